@@ -0,0 +1,59 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(has_alloc_error_hook)");
+    println!("cargo:rustc-check-cfg=cfg(has_panic_hook_info)");
+    println!("cargo:rustc-check-cfg=cfg(wasi_logging)");
+    println!("cargo:rustc-check-cfg=cfg(stderr)");
+
+    // Rust 1.81 renamed the panic hook's argument to `PanicHookInfo`, and
+    // deprecated the old `PanicInfo` name for it.
+    match rustc_minor_version() {
+        Some(minor) if minor >= 81 => println!("cargo:rustc-cfg=has_panic_hook_info"),
+        _ => {}
+    }
+
+    // `std::alloc::set_alloc_error_hook` is still unstable, so the
+    // `alloc-error-hook` feature needs a nightly toolchain to do anything.
+    if env::var_os("CARGO_FEATURE_ALLOC_ERROR_HOOK").is_some() && rustc_is_nightly() {
+        println!("cargo:rustc-cfg=has_alloc_error_hook");
+    }
+
+    // `wasi:logging` replaces `stderr`, but only where there is WASI to begin
+    // with.
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let wasi_logging = target_os == "wasi" && env::var_os("CARGO_FEATURE_WASI_LOGGING").is_some();
+    if wasi_logging {
+        println!("cargo:rustc-cfg=wasi_logging");
+    }
+
+    // Whether the `native` backend writes panics to `stderr`, rather than to
+    // the host's logging or Emscripten's console.
+    if !wasi_logging && target_os != "emscripten" {
+        println!("cargo:rustc-cfg=stderr");
+    }
+}
+
+fn rustc_version() -> Option<String> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    String::from_utf8(output.stdout).ok()
+}
+
+fn rustc_minor_version() -> Option<u32> {
+    let version = rustc_version()?;
+    let mut pieces = version.split_whitespace().nth(1)?.split('.');
+    if pieces.next()? != "1" {
+        return None;
+    }
+    pieces.next()?.parse().ok()
+}
+
+fn rustc_is_nightly() -> bool {
+    match rustc_version() {
+        Some(version) => version.contains("-nightly") || version.contains("-dev"),
+        None => false,
+    }
+}
@@ -0,0 +1,64 @@
+// The IndexedDB side of `IndexedDbReporter`, which is asynchronous all the way
+// down and much easier to write in JavaScript.
+
+const DATABASE = "console_error_panic_hook";
+const STORE = "reports";
+
+function open() {
+  return new Promise((resolve, reject) => {
+    const request = indexedDB.open(DATABASE, 1);
+    request.onupgradeneeded = () => {
+      request.result.createObjectStore(STORE, { autoIncrement: true });
+    };
+    request.onsuccess = () => resolve(request.result);
+    request.onerror = () => reject(request.error);
+  });
+}
+
+// Run `f` on the store in a transaction, resolving to the result of the
+// request it returns, if any, once the transaction is done.
+function transaction(mode, f) {
+  return open().then(
+    (db) =>
+      new Promise((resolve, reject) => {
+        const tx = db.transaction(STORE, mode);
+        const request = f(tx.objectStore(STORE));
+        tx.oncomplete = () => {
+          db.close();
+          resolve(request && request.result);
+        };
+        tx.onerror = tx.onabort = () => {
+          db.close();
+          reject(tx.error);
+        };
+      })
+  );
+}
+
+export function append(record, capacity) {
+  transaction("readwrite", (store) => {
+    store.add(record);
+    // Keys only ever grow, so the first ones are the oldest.
+    const keys = store.getAllKeys();
+    keys.onsuccess = () => {
+      const excess = keys.result.length - capacity;
+      if (excess > 0) {
+        store.delete(IDBKeyRange.upperBound(keys.result[excess - 1]));
+      }
+    };
+  }).catch(() => {});
+}
+
+export function getAll() {
+  return transaction("readonly", (store) => store.getAll());
+}
+
+export function getAllThen(callback) {
+  getAll().then(callback, () => callback([]));
+}
+
+export function clear() {
+  return transaction("readwrite", (store) => {
+    store.clear();
+  });
+}
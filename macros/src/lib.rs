@@ -0,0 +1,20 @@
+//! The `#[console_error_panic_hook::main]` attribute. Use it through the
+//! `macros` feature of `console_error_panic_hook`, which re-exports it.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::Nothing;
+use syn::{parse_macro_input, parse_quote, ItemFn};
+
+/// Set the panic hook, with `console_error_panic_hook::set_once`, before the
+/// body of the function runs.
+#[proc_macro_attribute]
+pub fn main(attr: TokenStream, item: TokenStream) -> TokenStream {
+    parse_macro_input!(attr as Nothing);
+    let mut function = parse_macro_input!(item as ItemFn);
+    function
+        .block
+        .stmts
+        .insert(0, parse_quote!(::console_error_panic_hook::set_once();));
+    quote!(#function).into()
+}
@@ -0,0 +1,57 @@
+use super::{imp, transport, PanicReport, Reporter, Transport};
+
+/// A reporter that sends panics to a remote endpoint with
+/// `navigator.sendBeacon`, which browsers deliver even as the page is being
+/// torn down, as it may well be after a panic.
+///
+/// Each panic is sent as a JSON object with the `message`, `payload`, `file`,
+/// `line`, `column`, `thread`, `stack`, `timestamp`, `uptime`, `memory`,
+/// `environment`, `build`, and `moduleName` of the
+/// [`PanicReport`](struct.PanicReport.html), in a `POST` request with a
+/// `text/plain` body, which doesn't need a CORS preflight. Browsers limit how
+/// much can be queued, typically to 64KiB, and silently drop beacons past
+/// that. Where there is no `sendBeacon`, and on non-wasm targets, this does
+/// nothing.
+///
+/// Used as a [`Transport`](trait.Transport.html), panics are sent as an array
+/// of such objects, even when there is only one in a batch.
+///
+/// ```
+/// use console_error_panic_hook::{BeaconReporter, ConsoleReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(BeaconReporter::new("https://example.com/panics")))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct BeaconReporter {
+    url: String,
+}
+
+impl BeaconReporter {
+    /// Create a reporter that sends panics to `url`.
+    pub fn new<S: Into<String>>(url: S) -> BeaconReporter {
+        BeaconReporter { url: url.into() }
+    }
+}
+
+impl Transport for BeaconReporter {
+    fn serialize(&self, reports: &[PanicReport]) -> Option<String> {
+        imp::to_json(reports, true)
+    }
+
+    fn deliver(&self, payload: &str) -> bool {
+        imp::send_beacon(&self.url, payload)
+    }
+}
+
+impl Reporter for BeaconReporter {
+    fn report(&self, report: &PanicReport) {
+        transport::send(self, imp::report_to_json(report, true));
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
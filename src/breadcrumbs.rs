@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use super::{imp, lock};
+
+/// Something the application did shortly before a panic, recorded with
+/// [`add_breadcrumb`](fn.add_breadcrumb.html).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct Breadcrumb {
+    pub(crate) category: String,
+    pub(crate) message: String,
+    pub(crate) timestamp: Option<f64>,
+}
+
+impl Breadcrumb {
+    /// What kind of event this was, such as `navigation` or `input`.
+    pub fn category(&self) -> &str {
+        &self.category
+    }
+
+    /// What happened.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// When it happened, in milliseconds since the Unix epoch, if the clock
+    /// could be read.
+    pub fn timestamp(&self) -> Option<f64> {
+        self.timestamp
+    }
+}
+
+/// How many breadcrumbs are kept by default.
+const DEFAULT_MAX_BREADCRUMBS: usize = 20;
+
+static MAX_BREADCRUMBS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_BREADCRUMBS);
+
+/// The most recent breadcrumbs, oldest first.
+static BREADCRUMBS: Mutex<VecDeque<Breadcrumb>> = Mutex::new(VecDeque::new());
+
+/// Record that the application did something, so that a panic soon after
+/// says what led up to it.
+///
+/// The last 20 breadcrumbs, from any thread, are kept (see
+/// [`set_max_breadcrumbs`](fn.set_max_breadcrumbs.html)), and every panic
+/// report includes them, as a "Breadcrumbs:" section of its message and as
+/// its [`breadcrumbs`](struct.PanicReport.html#method.breadcrumbs).
+///
+/// ```
+/// console_error_panic_hook::add_breadcrumb("navigation", "opened /settings");
+/// ```
+pub fn add_breadcrumb(category: &str, message: &str) {
+    let max = MAX_BREADCRUMBS.load(Ordering::Relaxed);
+    let mut breadcrumbs = lock(&BREADCRUMBS);
+    while !breadcrumbs.is_empty() && breadcrumbs.len() >= max {
+        breadcrumbs.pop_front();
+    }
+    if max > 0 {
+        breadcrumbs.push_back(Breadcrumb {
+            category: category.to_string(),
+            message: message.to_string(),
+            timestamp: imp::now(),
+        });
+    }
+}
+
+/// Keep the last `max` breadcrumbs rather than the last 20. Zero turns
+/// breadcrumbs off. Any beyond the new maximum are dropped, oldest first.
+pub fn set_max_breadcrumbs(max: usize) {
+    MAX_BREADCRUMBS.store(max, Ordering::Relaxed);
+    let mut breadcrumbs = lock(&BREADCRUMBS);
+    while breadcrumbs.len() > max {
+        breadcrumbs.pop_front();
+    }
+}
+
+/// Forget every breadcrumb recorded so far, for example once the work they
+/// describe has finished.
+pub fn clear_breadcrumbs() {
+    lock(&BREADCRUMBS).clear();
+}
+
+/// Whether there are any breadcrumbs to report.
+pub(crate) fn any() -> bool {
+    !lock(&BREADCRUMBS).is_empty()
+}
+
+/// Copies of the breadcrumbs, oldest first.
+pub(crate) fn recent() -> Vec<Breadcrumb> {
+    lock(&BREADCRUMBS).iter().cloned().collect()
+}
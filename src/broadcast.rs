@@ -0,0 +1,48 @@
+use super::{imp, PanicReport, Reporter};
+
+/// A reporter that publishes panics on a named `BroadcastChannel`, which any
+/// other tab, worker, or dashboard of the same origin can subscribe to.
+///
+/// ```
+/// use console_error_panic_hook::{BroadcastReporter, ConsoleReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(BroadcastReporter::new("rust-panics")))
+///     .install();
+/// ```
+///
+/// Each message is a plain object with the `message`, `payload`, `file`,
+/// `line`, `column`, `thread`, `stack`, `timestamp`, `uptime`, `memory`,
+/// `environment`, `build`, and `moduleName` of the
+/// [`PanicReport`](struct.PanicReport.html):
+///
+/// ```js
+/// new BroadcastChannel("rust-panics").onmessage = ({ data }) => {
+///   console.error(`Some tab panicked:\n\n${data.message}`);
+/// };
+/// ```
+///
+/// The channel is only open while a panic is being published, so it doesn't
+/// keep Node's or Deno's event loop alive. Where there is no
+/// `BroadcastChannel`, and on non-wasm targets, this does nothing.
+#[derive(Clone, Debug)]
+pub struct BroadcastReporter {
+    channel: String,
+}
+
+impl BroadcastReporter {
+    /// Create a reporter that publishes panics on the `BroadcastChannel`
+    /// named `channel`.
+    pub fn new<S: Into<String>>(channel: S) -> BroadcastReporter {
+        BroadcastReporter {
+            channel: channel.into(),
+        }
+    }
+}
+
+impl Reporter for BroadcastReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::broadcast(report, &self.channel);
+    }
+}
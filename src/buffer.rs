@@ -0,0 +1,107 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::{lock, PanicReport, Reporter};
+
+/// A queue of panic reports that drops the oldest ones once it is full.
+#[derive(Debug)]
+pub(crate) struct RingBuffer {
+    reports: VecDeque<PanicReport>,
+}
+
+impl RingBuffer {
+    pub(crate) const fn new() -> RingBuffer {
+        RingBuffer {
+            reports: VecDeque::new(),
+        }
+    }
+
+    /// Add `report`, making room by dropping the oldest reports if there are
+    /// already `capacity` of them.
+    pub(crate) fn push(&mut self, report: PanicReport, capacity: usize) {
+        while !self.reports.is_empty() && self.reports.len() >= capacity {
+            self.reports.pop_front();
+        }
+        if capacity > 0 {
+            self.reports.push_back(report);
+        }
+    }
+
+    /// Take every report, oldest first.
+    pub(crate) fn drain(&mut self) -> Vec<PanicReport> {
+        self.reports.drain(..).collect()
+    }
+
+    /// Copies of the `n` oldest reports.
+    pub(crate) fn oldest(&self, n: usize) -> Vec<PanicReport> {
+        self.reports.iter().take(n).cloned().collect()
+    }
+
+    /// Drop the `n` oldest reports.
+    pub(crate) fn pop_oldest(&mut self, n: usize) {
+        let n = n.min(self.reports.len());
+        self.reports.drain(..n);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+}
+
+/// Panics kept by [`BufferReporter`]s, until they are drained.
+static BUFFERED: Mutex<RingBuffer> = Mutex::new(RingBuffer::new());
+
+/// A reporter that keeps panics in memory for the embedder to collect later,
+/// rather than logging them.
+///
+/// This suits places with no usable `console` nor `fetch`, such as
+/// `AudioWorkletProcessor`s: poll [`BufferReporter::drain`] (or, with the
+/// `drain` feature, the exported `drainPanicReports()` JavaScript function)
+/// and pass the panics on, for example over the processor's `port` to the
+/// main thread. Only the most recent panics are kept.
+///
+/// ```
+/// use console_error_panic_hook::BufferReporter;
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(BufferReporter::new()))
+///     .install();
+///
+/// // Later, from some regular callback:
+/// for report in BufferReporter::drain() {
+///     // Send `report` somewhere...
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BufferReporter {
+    capacity: usize,
+}
+
+impl BufferReporter {
+    /// Create a reporter that keeps the 32 most recent panics.
+    pub fn new() -> BufferReporter {
+        BufferReporter::with_capacity(32)
+    }
+
+    /// Create a reporter that keeps the `capacity` most recent panics.
+    pub fn with_capacity(capacity: usize) -> BufferReporter {
+        BufferReporter { capacity }
+    }
+
+    /// Take the buffered panics, oldest first.
+    pub fn drain() -> Vec<PanicReport> {
+        lock(&BUFFERED).drain()
+    }
+}
+
+impl Default for BufferReporter {
+    fn default() -> BufferReporter {
+        BufferReporter::new()
+    }
+}
+
+impl Reporter for BufferReporter {
+    fn report(&self, report: &PanicReport) {
+        lock(&BUFFERED).push(report.clone(), self.capacity);
+    }
+}
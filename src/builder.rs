@@ -0,0 +1,1083 @@
+use std::fmt;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use super::fixed::Buffer;
+use super::redact::Redactions;
+#[cfg(feature = "dwarf")]
+use super::DebugInfo;
+use super::{
+    breadcrumbs, console, context, deliver, hook_impl, imp, lock, report, stack, BuildInfo,
+    FunctionNames, Hook, Outcome, PanicHookInfo, PanicReport, Reporter, Runtime, ScopedHook,
+    Settings, ENABLED, PREVIOUS_HOOK,
+};
+
+/// How much detail to include when reporting a panic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only report the panic message and its location. With nothing else
+    /// configured to go along with it, the message is formatted on the stack
+    /// and logged without allocating.
+    Short,
+    /// Report the panic message along with a stack trace where one is
+    /// available. On `wasm32-unknown-unknown` this is the JavaScript stack
+    /// captured with `new Error().stack`, and on other targets it is a native
+    /// backtrace when `RUST_BACKTRACE` is set (see
+    /// [`HookBuilder::with_backtrace`](struct.HookBuilder.html#method.with_backtrace)).
+    #[default]
+    Full,
+}
+
+/// What to do with the Node process once a panic has been reported. See
+/// [`HookBuilder::with_node_exit`](struct.HookBuilder.html#method.with_node_exit).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NodeExit {
+    /// Leave the process alone.
+    #[default]
+    Ignore,
+    /// Set `process.exitCode`, so the process fails with this code whenever
+    /// it does exit.
+    SetExitCode(i32),
+    /// Call `process.exit` with this code, ending the process right away.
+    Exit(i32),
+}
+
+/// A function that renders a panic as the text to report.
+pub type Formatter = fn(&PanicHookInfo) -> String;
+
+/// Whether there is only the one thread, which is how wasm is without atomics.
+const SINGLE_THREADED: bool = cfg!(all(target_family = "wasm", not(target_feature = "atomics")));
+
+/// The settings a configured panic hook reports panics with.
+#[derive(Default)]
+pub(crate) struct Config {
+    pub(crate) prefix: Option<String>,
+    pub(crate) module_name: Option<String>,
+    pub(crate) verbosity: Verbosity,
+    pub(crate) formatter: Option<Formatter>,
+    pub(crate) reporters: Vec<Box<dyn Reporter>>,
+    pub(crate) chain_previous: bool,
+    pub(crate) keep_hook_frames: bool,
+    pub(crate) keep_glue_frames: bool,
+    pub(crate) function_names: Option<FunctionNames>,
+    pub(crate) source_link: Option<String>,
+    pub(crate) timestamp: bool,
+    pub(crate) memory: bool,
+    pub(crate) environment: bool,
+    pub(crate) build_info: Option<BuildInfo>,
+    pub(crate) max_frames: Option<usize>,
+    pub(crate) max_length: Option<usize>,
+    pub(crate) redactions: Redactions,
+    pub(crate) dedup: bool,
+    /// The last panic reported when deduplicating, and how many times it has
+    /// repeated since.
+    pub(crate) last_panic: Mutex<Option<(String, usize)>>,
+    pub(crate) first_panic_only: bool,
+    pub(crate) panics: AtomicUsize,
+    pub(crate) rate_limit: Option<(usize, Option<Duration>)>,
+    /// When the current rate limit window started, if there is a window, and
+    /// how many panics have happened in it.
+    pub(crate) rate_window: Mutex<(Option<f64>, usize)>,
+    pub(crate) force_backtrace: bool,
+    pub(crate) backtrace_switch: bool,
+    pub(crate) abort: bool,
+    pub(crate) node_exit: NodeExit,
+    pub(crate) debugger: bool,
+    pub(crate) throw: bool,
+    pub(crate) unhandled_rejections: bool,
+    pub(crate) trap_correlation: bool,
+    /// The last panic reported, while waiting for the trap that follows it.
+    pub(crate) last_report: Mutex<Option<PanicReport>>,
+    pub(crate) runtime: Option<Runtime>,
+    pub(crate) runtime_config: bool,
+    #[cfg(feature = "dwarf")]
+    pub(crate) debug_info: Option<DebugInfo>,
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("prefix", &self.prefix)
+            .field("module_name", &self.module_name)
+            .field("verbosity", &self.verbosity)
+            .field("formatter", &self.formatter)
+            .field("reporters", &self.reporters.len())
+            .field("chain_previous", &self.chain_previous)
+            .field("keep_hook_frames", &self.keep_hook_frames)
+            .field("keep_glue_frames", &self.keep_glue_frames)
+            .field("function_names", &self.function_names.is_some())
+            .field("source_link", &self.source_link)
+            .field("timestamp", &self.timestamp)
+            .field("memory", &self.memory)
+            .field("environment", &self.environment)
+            .field("build_info", &self.build_info)
+            .field("max_frames", &self.max_frames)
+            .field("max_length", &self.max_length)
+            .field("redactions", &self.redactions.len())
+            .field("dedup", &self.dedup)
+            .field("first_panic_only", &self.first_panic_only)
+            .field("rate_limit", &self.rate_limit)
+            .field("force_backtrace", &self.force_backtrace)
+            .field("backtrace_switch", &self.backtrace_switch)
+            .field("abort", &self.abort)
+            .field("node_exit", &self.node_exit)
+            .field("debugger", &self.debugger)
+            .field("throw", &self.throw)
+            .field("unhandled_rejections", &self.unhandled_rejections)
+            .field("trap_correlation", &self.trap_correlation)
+            .field("runtime", &self.runtime)
+            .field("runtime_config", &self.runtime_config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Config {
+    /// The panic's message, with our prefix if we have one.
+    ///
+    /// Unless the panic happened on the main thread, the default message names
+    /// the thread, like the standard library's hook does. Without atomics,
+    /// wasm only has the one thread, which goes unnamed.
+    pub(crate) fn message(&self, info: &PanicHookInfo, thread: &str) -> String {
+        let mut msg = String::new();
+        let _ = self.write_message(&mut msg, info, thread);
+        msg
+    }
+
+    fn write_message<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        info: &PanicHookInfo,
+        thread: &str,
+    ) -> fmt::Result {
+        self.write_label(out)?;
+        match self.formatter {
+            Some(formatter) => out.write_str(&formatter(info)),
+            None if thread == "main" || SINGLE_THREADED => write!(out, "{}", info),
+            None => write!(out, "thread '{}' {}", thread, info),
+        }
+    }
+
+    /// Our prefix and module name, ahead of a message.
+    fn write_label<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        if let Some(ref prefix) = self.prefix {
+            write!(out, "{} ", prefix)?;
+        }
+        if let Some(ref module_name) = self.module_name {
+            write!(out, "[{}] ", module_name)?;
+        }
+        Ok(())
+    }
+
+    /// Format the message for `info` into `buffer`, without allocating, if
+    /// there is nothing to report but that message. Returns whether that is
+    /// the case and the whole message fit.
+    pub(crate) fn fast_message(&self, info: &PanicHookInfo, buffer: &mut Buffer) -> bool {
+        let runtime = self.runtime.unwrap_or_else(Runtime::detect);
+        // Under Node, the message is written to `stderr` rather than logged,
+        // and with `color`, a terminal or Deno's console get it colored.
+        let plain_console = match runtime {
+            Runtime::Node => false,
+            Runtime::Deno | Runtime::Native => !cfg!(feature = "color"),
+            Runtime::Browser | Runtime::Worker | Runtime::Unknown => true,
+        };
+        let message_only = plain_console
+            && !self.switch().unwrap_or(self.verbosity == Verbosity::Full)
+            && self.formatter.is_none()
+            && self.reporters.is_empty()
+            && self.source_link.is_none()
+            && !self.timestamp
+            && !self.memory
+            && !self.environment
+            && self.build_info.is_none()
+            && self.max_length.is_none()
+            && self.redactions.is_empty()
+            && !self.trap_correlation
+            && (self.switch() == Some(false)
+                || !breadcrumbs::any() && !context::any() && !context::has_providers());
+        if !message_only {
+            return false;
+        }
+        let thread = thread::current();
+        let name = match thread.name() {
+            Some(name) => name,
+            None if SINGLE_THREADED => "",
+            None => return false,
+        };
+        self.write_message(buffer, info, name).is_ok()
+    }
+
+    /// Whether the `RUST_BACKTRACE` switch is on, if we follow it rather than
+    /// our verbosity.
+    fn switch(&self) -> Option<bool> {
+        if self.backtrace_switch {
+            Some(imp::backtrace_switch().unwrap_or(false))
+        } else {
+            None
+        }
+    }
+
+    /// Override our configuration with `settings`.
+    fn apply(&mut self, settings: Settings) {
+        if let Some(verbosity) = settings.verbosity {
+            self.verbosity = verbosity;
+        }
+        if settings.prefix.is_some() {
+            self.prefix = settings.prefix;
+        }
+        if settings.remote == Some(false) {
+            self.reporters.retain(|reporter| !reporter.is_remote());
+        }
+    }
+
+    /// Whether this is a panic after the first while only the first is
+    /// reported. The second panic logs that the instance must be reloaded.
+    pub(crate) fn is_after_first(&self) -> bool {
+        if !self.first_panic_only {
+            return false;
+        }
+        match self.panics.fetch_add(1, Ordering::Relaxed) {
+            0 => false,
+            1 => {
+                let banner = "this wasm instance has panicked and must be reloaded, \
+                              not reporting any more panics";
+                imp::console_error(&self.prefixed(banner.to_string()));
+                true
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether `panic` is the same panic as the one reported last, in which
+    /// case it is only counted. Once a different panic comes along, a summary
+    /// of the repeats is logged before it.
+    pub(crate) fn is_repeat(&self, panic: &dyn fmt::Display) -> bool {
+        if !self.dedup {
+            return false;
+        }
+        let key = panic.to_string();
+        let mut last_panic = lock(&self.last_panic);
+        match *last_panic {
+            Some((ref last, ref mut repeats)) if *last == key => {
+                *repeats += 1;
+                return true;
+            }
+            Some((_, repeats)) if repeats > 0 => {
+                let plural = if repeats == 1 { "" } else { "s" };
+                let summary = format!("previous panic repeated {} time{}", repeats, plural);
+                imp::console_error(&self.prefixed(summary));
+            }
+            _ => {}
+        }
+        *last_panic = Some((key, 0));
+        false
+    }
+
+    /// Whether reporting another panic would go over our rate limit. The first
+    /// panic over it logs a note that further panics are not reported.
+    pub(crate) fn is_rate_limited(&self) -> bool {
+        let (max_reports, window) = match self.rate_limit {
+            Some(rate_limit) => rate_limit,
+            None => return false,
+        };
+        let mut rate_window = lock(&self.rate_window);
+        if let (Some(window), Some(now)) = (window, imp::now()) {
+            match rate_window.0 {
+                Some(start) if now - start < window.as_secs_f64() * 1000.0 => {}
+                _ => *rate_window = (Some(now), 0),
+            }
+        }
+        rate_window.1 += 1;
+        if rate_window.1 == max_reports + 1 {
+            let per = match window {
+                Some(window) => format!(" per {:?}", window),
+                None => String::new(),
+            };
+            let note = format!(
+                "reported {} panics{}, not reporting any more",
+                max_reports, per
+            );
+            imp::console_error(&self.prefixed(note));
+        }
+        rate_window.1 > max_reports
+    }
+
+    /// `msg`, with our prefix if we have one.
+    fn prefixed(&self, msg: String) -> String {
+        match self.prefix {
+            Some(ref prefix) => format!("{} {}", prefix, msg),
+            None => msg,
+        }
+    }
+
+    /// Report an unhandled promise rejection from wasm, unless reporting is
+    /// disabled or it is filtered out like a panic would be.
+    fn report_rejection(&self, reason: &str, stack: Option<String>) {
+        if !ENABLED.load(Ordering::Relaxed) {
+            return;
+        }
+        if self.is_after_first() || self.is_repeat(&reason) || self.is_rate_limited() {
+            return;
+        }
+        deliver(self, &self.rejection_report(reason, stack));
+    }
+
+    /// A report of an unhandled promise rejection from wasm, with the
+    /// rejection's `reason` as its payload.
+    fn rejection_report(&self, reason: &str, stack: Option<String>) -> PanicReport {
+        let mut reason = reason.to_string();
+        self.redactions.apply(&mut reason);
+        let mut msg = String::new();
+        let _ = self.write_label(&mut msg);
+        msg.push_str("unhandled rejection from wasm: ");
+        msg.push_str(&reason);
+        if let Some(max_length) = self.max_length {
+            truncate(&mut msg, max_length);
+            truncate(&mut reason, max_length);
+        }
+        let mut report = PanicReport::without_location(String::new(), Some(reason));
+        report.stack = stack.map(|stack| stack::process(self, stack));
+        self.add_sections(report, msg)
+    }
+
+    /// Log that the module trapped with `error` because of the last panic we
+    /// reported, if there is one we haven't linked a trap to yet. Returns
+    /// whether that was logged.
+    fn correlate_trap(&self, error: &str) -> bool {
+        let report = match lock(&self.last_report).take() {
+            Some(report) => report,
+            None => return false,
+        };
+        // Just the panic's own message, without the stack and such after it.
+        let panic = report.message().split("\n\n").next().unwrap_or_default();
+        let msg = format!(
+            "Uncaught {}, which the module trapped with after this panic:\n{}",
+            error, panic
+        );
+        imp::console_error(&msg)
+    }
+
+    /// The message of a panic on `thread`, redacted and cut to our maximum
+    /// length: the start of the message of its report, before the sections
+    /// appended to it.
+    fn headline(&self, info: &PanicHookInfo, thread: &str) -> String {
+        let mut msg = self.message(info, thread);
+        // Redact before truncating, which could cut what is to be redacted
+        // short of matching.
+        self.redactions.apply(&mut msg);
+        if let Some(max_length) = self.max_length {
+            truncate(&mut msg, max_length);
+        }
+        msg
+    }
+
+    /// Everything there is to report about a panic: its message and,
+    /// depending on our verbosity, the stack, which is also appended to the
+    /// message.
+    pub(crate) fn report(&self, info: &PanicHookInfo) -> PanicReport {
+        let mut report = PanicReport::new(info);
+        let switch = self.switch();
+        let msg = self.headline(info, &report.thread);
+        if let Some(ref mut payload) = report.payload {
+            self.redactions.apply(payload);
+            if let Some(max_length) = self.max_length {
+                truncate(payload, max_length);
+            }
+        }
+
+        if let Some(ref template) = self.source_link {
+            report.source_link = info.location().map(|location| {
+                template
+                    .replace("{file}", location.file())
+                    .replace("{line}", &location.line().to_string())
+                    .replace("{column}", &location.column().to_string())
+            });
+        }
+
+        if switch.unwrap_or(self.verbosity == Verbosity::Full) {
+            let force = self.force_backtrace || switch == Some(true);
+            report.stack = imp::capture_stack(force).map(|stack| stack::process(self, stack));
+        }
+
+        self.add_sections(report, msg)
+    }
+
+    /// Finish `report`, of a panic or rejection with the message `msg`, by
+    /// gathering what else there is to report about it, and appending a
+    /// section for each part of it to the message: its context, build, time,
+    /// state, breadcrumbs, memory size, source link, and stack.
+    fn add_sections(&self, mut report: PanicReport, mut msg: String) -> PanicReport {
+        let switch = self.switch();
+
+        // With the switch off, there is only the message to report.
+        if switch != Some(false) {
+            report.context = context::current();
+            report.state = context::provided();
+            report.breadcrumbs = breadcrumbs::recent();
+        }
+        // Everything that may hold application data is redacted once, before
+        // it is added to the message.
+        self.redactions.apply_to_sections(&mut report);
+        if let Some(context) = report::context_section(&report) {
+            msg.push_str("\n\n");
+            msg.push_str(&context);
+        }
+
+        report.module_name = self.module_name.clone();
+        report.build = self.build_info.clone();
+        if let Some(build) = report::build_section(&report) {
+            msg.push_str("\n\n");
+            msg.push_str(&build);
+        }
+
+        if self.timestamp {
+            report.uptime = imp::uptime();
+            if let Some(time) = report::time_section(&report) {
+                msg.push_str("\n\n");
+                msg.push_str(&time);
+            }
+        }
+
+        if let Some(state) = report::state_section(&report) {
+            msg.push_str("\n\n");
+            msg.push_str(&state);
+        }
+
+        if let Some(breadcrumbs) = report::breadcrumbs_section(&report) {
+            msg.push_str("\n\n");
+            msg.push_str(&breadcrumbs);
+        }
+
+        if self.memory || switch == Some(true) {
+            report.memory = report::memory_size();
+            if let Some(memory) = report::memory_section(&report) {
+                msg.push_str("\n\n");
+                msg.push_str(&memory);
+            }
+        }
+
+        if self.environment || switch == Some(true) {
+            report.environment = imp::environment();
+        }
+
+        if let Some(ref mut link) = report.source_link {
+            self.redactions.apply(link);
+            msg.push_str("\n\nSource: ");
+            msg.push_str(link);
+        }
+
+        if let Some(ref mut stack) = report.stack {
+            self.redactions.apply(stack);
+            // Add the error stack to our message.
+            //
+            // This ensures that even if the `console` implementation doesn't
+            // include stacks for `console.error`, the stack is still available
+            // for the user. Additionally, Firefox's console tries to clean up
+            // stack traces, and ruins Rust symbols in the process
+            // (https://bugzilla.mozilla.org/show_bug.cgi?id=1519569) but since
+            // it only touches the logged message's associated stack, and not
+            // the message's contents, by including the stack in the message
+            // contents we make sure it is available to the user. It also means
+            // that reporters which only look at the message still get the
+            // stack.
+            msg.push_str("\n\nStack:\n\n");
+            msg.push_str(stack);
+        }
+
+        report.message = msg;
+        report
+    }
+}
+
+/// A builder for configuring the panic hook before installing it.
+///
+/// Create one with
+/// [`console_error_panic_hook::builder()`](fn.builder.html).
+#[derive(Debug, Default)]
+pub struct HookBuilder {
+    config: Config,
+}
+
+impl HookBuilder {
+    /// Create a new builder with the default configuration, which behaves
+    /// exactly like [`hook`](fn.hook.html).
+    pub fn new() -> HookBuilder {
+        HookBuilder::default()
+    }
+
+    /// Prepend `prefix` to every reported panic message, so that panics from
+    /// this module are easy to pick out of busy console output.
+    pub fn with_prefix<S: Into<String>>(mut self, prefix: S) -> HookBuilder {
+        self.config.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Name the wasm module this hook is installed from, for pages that load
+    /// several. The name goes in brackets at the start of every message, after
+    /// any prefix, and is recorded as the report's
+    /// [`module_name`](struct.PanicReport.html#method.module_name).
+    ///
+    /// ```
+    /// console_error_panic_hook::builder()
+    ///     .with_module_name("editor")
+    ///     .install();
+    /// ```
+    pub fn with_module_name<S: Into<String>>(mut self, module_name: S) -> HookBuilder {
+        self.config.module_name = Some(module_name.into());
+        self
+    }
+
+    /// Set how much detail is included when reporting a panic. Defaults to
+    /// `Verbosity::Full`.
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> HookBuilder {
+        self.config.verbosity = verbosity;
+        self
+    }
+
+    /// Render panics with `formatter` instead of the default `PanicHookInfo`
+    /// display. The hook still takes care of the prefix, the stack, and
+    /// delivering the message to the console.
+    pub fn with_formatter(mut self, formatter: Formatter) -> HookBuilder {
+        self.config.formatter = Some(formatter);
+        self
+    }
+
+    /// Hand panics to `reporter` instead of logging them to the console.
+    ///
+    /// This may be called multiple times, and every reporter receives every
+    /// panic, in the order they were added. To keep logging to the console
+    /// alongside other reporters, add a
+    /// [`ConsoleReporter`](struct.ConsoleReporter.html) too.
+    ///
+    /// ```
+    /// use console_error_panic_hook::{ConsoleReporter, FnReporter};
+    ///
+    /// console_error_panic_hook::builder()
+    ///     .with_reporter(Box::new(ConsoleReporter::new()))
+    ///     .with_reporter(Box::new(FnReporter::new(|msg: &str| {
+    ///         // Show `msg` in the in-page debug panel...
+    ///     })))
+    ///     .install();
+    /// ```
+    pub fn with_reporter(mut self, reporter: Box<dyn Reporter>) -> HookBuilder {
+        self.config.reporters.push(reporter);
+        self
+    }
+
+    /// Whether to call the previously installed panic hook after reporting a
+    /// panic, instead of replacing it. Defaults to `false`.
+    ///
+    /// This lets this hook compose with other crates that install their own
+    /// panic hooks.
+    pub fn with_previous_hook(mut self, chain: bool) -> HookBuilder {
+        self.config.chain_previous = chain;
+        self
+    }
+
+    /// Whether to keep the frames from this hook and the standard library's
+    /// panic machinery at the top of captured stacks. Defaults to `false`, so
+    /// that the stack starts at the frame that panicked.
+    pub fn with_hook_frames(mut self, keep: bool) -> HookBuilder {
+        self.config.keep_hook_frames = keep;
+        self
+    }
+
+    /// Whether to keep frames from `wasm-bindgen`'s generated glue (`__wbg_*`
+    /// shims and the like) in captured stacks. Defaults to `false`; turn it on
+    /// when debugging the glue itself.
+    pub fn with_glue_frames(mut self, keep: bool) -> HookBuilder {
+        self.config.keep_glue_frames = keep;
+        self
+    }
+
+    /// Whether to always capture a native backtrace on non-wasm targets, even
+    /// when `RUST_BACKTRACE` isn't set. Defaults to `false`.
+    ///
+    /// This gives code that runs both in the browser and natively, such as in
+    /// unit tests, comparable diagnostics on both. It has no effect on wasm,
+    /// where the JavaScript stack is always captured.
+    pub fn with_backtrace(mut self, force: bool) -> HookBuilder {
+        self.config.force_backtrace = force;
+        self
+    }
+
+    /// Whether to decide how much to report at panic time, from a
+    /// `RUST_BACKTRACE` switch, the way native programs do. Defaults to
+    /// `false`.
+    ///
+    /// When on, panics are reported with just their message, without even
+    /// their [context](fn.context.html), state, or breadcrumbs, unless the
+    /// switch is set to anything but `0`, in which case they come with those,
+    /// the stack, the [environment](#method.with_environment), and the
+    /// [memory size](#method.with_memory) too. This takes the place of the
+    /// [verbosity](#method.with_verbosity), so a deployed build stays quiet
+    /// until someone investigating a panic flips the switch from the
+    /// devtools console:
+    ///
+    /// ```js
+    /// globalThis.RUST_BACKTRACE = "1";
+    /// ```
+    ///
+    /// On non-wasm targets, the `RUST_BACKTRACE` environment variable is the
+    /// switch.
+    pub fn with_backtrace_switch(mut self, backtrace_switch: bool) -> HookBuilder {
+        self.config.backtrace_switch = backtrace_switch;
+        self
+    }
+
+    /// Whether to abort the process once a panic has been reported on non-wasm
+    /// targets. Defaults to `false`.
+    ///
+    /// A panic traps the whole module on wasm, but natively it may unwind and
+    /// be caught, or only end the panicking thread. Turn this on to make every
+    /// panic fail the process instead, for example in CI. Any chained previous
+    /// hook still runs first. It has no effect on wasm.
+    pub fn with_abort(mut self, abort: bool) -> HookBuilder {
+        self.config.abort = abort;
+        self
+    }
+
+    /// What to do with the process, under Node, once a panic has been
+    /// reported. Defaults to `NodeExit::Ignore`.
+    ///
+    /// A panic traps the module, but the exception that results may well be
+    /// caught, leaving a CLI tool compiled to wasm to exit with a successful
+    /// code. Set an exit code here so wrapper scripts and CI notice the
+    /// failure. Any chained previous hook still runs first. This has no effect
+    /// outside of Node; see [`with_abort`](#method.with_abort) for native
+    /// targets.
+    ///
+    /// ```
+    /// use console_error_panic_hook::NodeExit;
+    ///
+    /// console_error_panic_hook::builder()
+    ///     .with_node_exit(NodeExit::SetExitCode(101))
+    ///     .install();
+    /// ```
+    pub fn with_node_exit(mut self, node_exit: NodeExit) -> HookBuilder {
+        self.config.node_exit = node_exit;
+        self
+    }
+
+    /// Whether to pause on a JavaScript `debugger` statement once a panic has
+    /// been reported. Defaults to `false`.
+    ///
+    /// With devtools open, this stops execution before the module traps, so
+    /// that wasm memory and the JavaScript stack can still be inspected.
+    /// Without devtools, the statement does nothing. It is evaluated with
+    /// `new Function`, so a Content Security Policy without `'unsafe-eval'`
+    /// prevents it. Any chained previous hook still runs first. This has no
+    /// effect on non-wasm targets.
+    pub fn with_debugger(mut self, debugger: bool) -> HookBuilder {
+        self.config.debugger = debugger;
+        self
+    }
+
+    /// Whether to throw a JavaScript `Error` with the panic's message once it
+    /// has been reported, instead of letting the module trap. Defaults to
+    /// `false`. The message is the one reported, redacted and cut to the
+    /// maximum length alike, without the sections appended to it.
+    ///
+    /// Code calling into the module then catches an error that says what went
+    /// wrong, rather than a `RuntimeError: unreachable` with the message only
+    /// in the console, which is easy to miss, or to lose to a bundler's
+    /// overlay. The exception skips the rest of the panic, so the module is
+    /// left mid-panic: any later panic traps without being reported. Reload
+    /// the module after catching one. This has no effect on non-wasm targets.
+    pub fn with_throw(mut self, throw: bool) -> HookBuilder {
+        self.config.throw = throw;
+        self
+    }
+
+    /// Whether to also report unhandled promise rejections that come from
+    /// wasm, when the hook is built or installed. Defaults to `false`.
+    ///
+    /// Errors escaping futures spawned with `wasm_bindgen_futures` often end
+    /// up as unhandled rejections, which the console shows without any Rust
+    /// context. With this on, a rejection whose reason is a
+    /// `WebAssembly.RuntimeError`, or was thrown with wasm frames on its
+    /// stack, goes to the console or reporters like a panic does, with a
+    /// message starting "unhandled rejection from wasm:" and the reason's
+    /// stack, and the same sections as a panic's report. The same options
+    /// filter them too, such as [`set_enabled`](fn.set_enabled.html),
+    /// deduplicating, and rate limiting. Other rejections are left alone.
+    ///
+    /// This listens for the global `unhandledrejection` event, in browsers,
+    /// workers, and Deno. The listener stays registered once added. It has no
+    /// effect under Node, or on non-wasm targets.
+    pub fn with_unhandled_rejections(mut self, unhandled_rejections: bool) -> HookBuilder {
+        self.config.unhandled_rejections = unhandled_rejections;
+        self
+    }
+
+    /// Whether to link the `RuntimeError: unreachable` that a panic traps
+    /// the module with back to the panic, when the error goes uncaught.
+    /// Defaults to `false`.
+    ///
+    /// Otherwise, the console shows the panic and, some way below it, an
+    /// uncaught error that says nothing of where it came from. With this on,
+    /// a global `error` listener logs that error along with the message of
+    /// the panic behind it instead, and the browser's own entry for it is
+    /// suppressed. Errors that don't follow a reported panic are left alone.
+    ///
+    /// The listener is added when the hook is built or installed, and stays
+    /// registered. It has no effect under Node, where the global isn't an
+    /// `EventTarget`, or on non-wasm targets.
+    pub fn with_trap_correlation(mut self, trap_correlation: bool) -> HookBuilder {
+        self.config.trap_correlation = trap_correlation;
+        self
+    }
+
+    /// Keep at most `max_frames` frames of captured stacks, replacing the rest
+    /// with a "… N more frames" marker, so that deep recursion doesn't flood
+    /// the console or remote reporters.
+    pub fn with_max_frames(mut self, max_frames: usize) -> HookBuilder {
+        self.config.max_frames = Some(max_frames);
+        self
+    }
+
+    /// Cut panic messages and payloads longer than `max_length` bytes short,
+    /// ending them with a "… [N more bytes]" marker, so that a panic with a
+    /// huge `Debug` dump in its message doesn't freeze devtools or go over
+    /// the size limits of remote reporters. What the hook appends to the
+    /// message, such as the stack, is not counted.
+    pub fn with_max_length(mut self, max_length: usize) -> HookBuilder {
+        self.config.max_length = Some(max_length);
+        self
+    }
+
+    /// Replace every occurrence of `literal` in reported panics with
+    /// `[redacted]`. This may be called multiple times.
+    ///
+    /// Panic messages sometimes embed user content, such as file names or
+    /// tokens, that must not reach the console or remote reporters. Everything
+    /// in a report that can hold such content is redacted before any reporter
+    /// sees it: the message, payload, stack, context, state, and breadcrumbs.
+    ///
+    /// ```
+    /// console_error_panic_hook::builder()
+    ///     .with_redaction("hunter2")
+    ///     .install();
+    /// ```
+    pub fn with_redaction<S: Into<String>>(mut self, literal: S) -> HookBuilder {
+        self.config.redactions.add_literal(literal.into());
+        self
+    }
+
+    /// Replace every match of `pattern` in reported panics with
+    /// `[redacted]`, like [`with_redaction`](#method.with_redaction) does for
+    /// literal text. Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn with_redaction_pattern(mut self, pattern: ::regex::Regex) -> HookBuilder {
+        self.config.redactions.add_pattern(pattern);
+        self
+    }
+
+    /// Whether to only report the first panic. Defaults to `false`.
+    ///
+    /// After a panic, the wasm instance is left in a broken state, and
+    /// further calls into its exports fail in confusing ways. With this
+    /// enabled, the next panic logs a single "this wasm instance has panicked
+    /// and must be reloaded" message instead of its own report, and any
+    /// after that are not reported at all.
+    pub fn with_first_panic_only(mut self, first_panic_only: bool) -> HookBuilder {
+        self.config.first_panic_only = first_panic_only;
+        self
+    }
+
+    /// Whether to collapse identical consecutive panics. Defaults to `false`.
+    ///
+    /// A panic in a `requestAnimationFrame` or `setInterval` callback happens
+    /// again on every call, flooding the console. With this enabled, a panic
+    /// with the same message at the same location as the one before it is
+    /// not reported again. When a different panic follows, a "previous panic
+    /// repeated N times" summary is logged first.
+    pub fn with_dedup(mut self, dedup: bool) -> HookBuilder {
+        self.config.dedup = dedup;
+        self
+    }
+
+    /// Report at most `max_reports` panics, either in total or, given a
+    /// `window`, in each window of that length. Panics over the limit are
+    /// not reported, and the first of them logs a note saying so.
+    ///
+    /// This keeps a retry loop that panics over and over from sending
+    /// thousands of requests to remote reporters. Repeats collapsed by
+    /// [`with_dedup`](#method.with_dedup) don't count towards the limit.
+    /// Without a clock, as on `wasm32-unknown-unknown` without
+    /// `wasm-bindgen`, the window never ends.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// console_error_panic_hook::builder()
+    ///     .with_rate_limit(10, Some(Duration::from_secs(60)))
+    ///     .install();
+    /// ```
+    pub fn with_rate_limit(mut self, max_reports: usize, window: Option<Duration>) -> HookBuilder {
+        self.config.rate_limit = Some((max_reports, window));
+        self
+    }
+
+    /// Rewrite `wasm-function[1234]` frames in captured stacks using the given
+    /// table of function names.
+    pub fn with_function_names(mut self, names: FunctionNames) -> HookBuilder {
+        self.config.function_names = Some(names);
+        self
+    }
+
+    /// Add Rust source locations to the wasm frames of captured stacks, using
+    /// the given DWARF debugging information.
+    ///
+    /// Requires the `dwarf` feature.
+    #[cfg(feature = "dwarf")]
+    pub fn with_debug_info(mut self, debug_info: DebugInfo) -> HookBuilder {
+        self.config.debug_info = Some(debug_info);
+        self
+    }
+
+    /// Include a link to the panic's location in reports, built from
+    /// `template` by replacing `{file}`, `{line}`, and `{column}`.
+    ///
+    /// Anything else the link needs, such as the revision that was deployed,
+    /// can be baked into the template when it is built:
+    ///
+    /// ```
+    /// let template = format!(
+    ///     "https://github.com/org/repo/blob/{}/{{file}}#L{{line}}",
+    ///     option_env!("GIT_HASH").unwrap_or("main"),
+    /// );
+    /// console_error_panic_hook::builder()
+    ///     .with_source_link(template)
+    ///     .install();
+    /// ```
+    pub fn with_source_link<S: Into<String>>(mut self, template: S) -> HookBuilder {
+        self.config.source_link = Some(template.into());
+        self
+    }
+
+    /// Whether to include when the panic happened in its message, as a
+    /// "Time: ..." line with the UTC date and, where `performance.now()` is
+    /// available, the milliseconds since the page or process started.
+    /// Defaults to `false`.
+    ///
+    /// The latter is also recorded as the report's
+    /// [`uptime`](struct.PanicReport.html#method.uptime), which orders panics
+    /// even when the wall clock can't be trusted, and lines them up with
+    /// other entries in a performance profile.
+    pub fn with_timestamp(mut self, timestamp: bool) -> HookBuilder {
+        self.config.timestamp = timestamp;
+        self
+    }
+
+    /// Whether to include the size of the module's linear memory in panic
+    /// messages, as a "Memory: ..." line with the number of wasm pages. It is
+    /// also recorded as the report's
+    /// [`memory`](struct.PanicReport.html#method.memory). Defaults to
+    /// `false`.
+    ///
+    /// This shows at a glance whether a panic happened as the module neared
+    /// the 4GiB limit of `wasm32`, or whatever maximum it declares. It has no
+    /// effect on targets other than `wasm32`.
+    pub fn with_memory(mut self, memory: bool) -> HookBuilder {
+        self.config.memory = memory;
+        self
+    }
+
+    /// Whether to capture the user agent, platform, and hardware concurrency
+    /// when a panic happens, as the report's
+    /// [`environment`](struct.PanicReport.html#method.environment). Defaults
+    /// to `false`.
+    ///
+    /// This goes to reporters that send panics elsewhere, but stays out of
+    /// the message, since whoever reads the console knows where it runs.
+    pub fn with_environment(mut self, environment: bool) -> HookBuilder {
+        self.config.environment = environment;
+        self
+    }
+
+    /// Stamp every panic with `build_info`, as a "Build: ..." line in its
+    /// message and as the report's
+    /// [`build`](struct.PanicReport.html#method.build). See
+    /// [`BuildInfo`](struct.BuildInfo.html) for an example.
+    pub fn with_build_info(mut self, build_info: BuildInfo) -> HookBuilder {
+        self.config.build_info = Some(build_info);
+        self
+    }
+
+    /// Which runtime to report panics for. By default, this is detected when
+    /// the hook is built or installed.
+    ///
+    /// When no reporter was added with
+    /// [`with_reporter`](#method.with_reporter), the runtime decides how
+    /// panics are logged: under Node, for example, they are written straight
+    /// to `stderr`. Override it if detection gets it wrong, or to get another
+    /// runtime's behavior.
+    ///
+    /// ```
+    /// use console_error_panic_hook::Runtime;
+    ///
+    /// console_error_panic_hook::builder()
+    ///     .with_runtime(Runtime::Unknown)
+    ///     .install();
+    /// ```
+    pub fn with_runtime(mut self, runtime: Runtime) -> HookBuilder {
+        self.config.runtime = Some(runtime);
+        self
+    }
+
+    /// Whether to let the page override some settings when the hook is built
+    /// or installed, so that a deployed build can be made to report panics
+    /// differently without recompiling it. Defaults to `false`.
+    ///
+    /// Settings are read from the `CONSOLE_ERROR_PANIC_HOOK` global, then
+    /// from the page URL's `panic_hook` query parameter, which takes a
+    /// comma-separated list such as `?panic_hook=verbose,no-remote`:
+    ///
+    /// * `short` or `quiet`, and `full` or `verbose`, set the
+    ///   [verbosity](#method.with_verbosity).
+    /// * `no-remote` drops the reporters that send panics to a remote
+    ///   service, like [`FetchReporter`](struct.FetchReporter.html). See
+    ///   [`Reporter::is_remote`](trait.Reporter.html#method.is_remote).
+    ///
+    /// The global can hold such a list too, or an object, which may also set
+    /// the [prefix](#method.with_prefix):
+    ///
+    /// ```js
+    /// globalThis.CONSOLE_ERROR_PANIC_HOOK = {
+    ///   verbosity: "short",
+    ///   prefix: "[qa]",
+    ///   remote: false,
+    /// };
+    /// ```
+    ///
+    /// On non-wasm targets, the list is read from the
+    /// `CONSOLE_ERROR_PANIC_HOOK` environment variable instead.
+    pub fn with_runtime_config(mut self, runtime_config: bool) -> HookBuilder {
+        self.config.runtime_config = runtime_config;
+        self
+    }
+
+    /// Build the configured panic hook, for use with
+    /// [`std::panic::set_hook`](https://doc.rust-lang.org/nightly/std/panic/fn.set_hook.html).
+    ///
+    /// If the previous hook is being chained, it is taken with
+    /// [`std::panic::take_hook`](https://doc.rust-lang.org/nightly/std/panic/fn.take_hook.html)
+    /// at this point.
+    pub fn build(self) -> Hook {
+        let previous = if self.config.chain_previous {
+            Some(Arc::new(panic::take_hook()))
+        } else {
+            None
+        };
+        self.build_chained(previous)
+    }
+
+    fn build_chained(self, previous: Option<Arc<Hook>>) -> Hook {
+        let mut config = self.config;
+        config.runtime.get_or_insert_with(Runtime::detect);
+        if config.runtime_config {
+            config.apply(read_settings());
+        }
+        let config = Arc::new(config);
+        if config.unhandled_rejections {
+            let config = config.clone();
+            imp::on_unhandled_rejection(move |reason, stack| {
+                config.report_rejection(reason, stack);
+            });
+        }
+        if config.trap_correlation {
+            let config = config.clone();
+            imp::on_trap(move |error| config.correlate_trap(error));
+        }
+        Box::new(move |info| {
+            let report = match hook_impl(&config, info) {
+                Outcome::Reported(report) => Some(report),
+                Outcome::Unreported => None,
+                Outcome::Nested => return,
+            };
+            if let Some(ref previous) = previous {
+                previous(info);
+            }
+            if !ENABLED.load(Ordering::Relaxed) {
+                return;
+            }
+            if config.debugger {
+                imp::debugger();
+            }
+            if config.node_exit != NodeExit::Ignore {
+                imp::node_exit(config.node_exit);
+            }
+            if config.abort {
+                imp::abort();
+            }
+            if config.throw {
+                // Throw what was reported, without the sections after it, or
+                // what would have been for a panic that wasn't.
+                let msg = match report {
+                    Some(report) => console::sections(&report).swap_remove(0),
+                    None => config.headline(info, &report::current_thread()),
+                };
+                imp::throw(&msg);
+            }
+        })
+    }
+
+    /// Build the configured panic hook and install it with
+    /// [`std::panic::set_hook`](https://doc.rust-lang.org/nightly/std/panic/fn.set_hook.html),
+    /// replacing any previously installed hook.
+    ///
+    /// The replaced hook is saved, and [`unset`](fn.unset.html) puts it back.
+    pub fn install(self) {
+        let mut saved = lock(&PREVIOUS_HOOK);
+        let previous = Arc::new(panic::take_hook());
+        let chained = if self.config.chain_previous {
+            Some(previous.clone())
+        } else {
+            None
+        };
+        panic::set_hook(self.build_chained(chained));
+        *saved = Some(previous);
+    }
+
+    /// Install the configured panic hook until the returned guard is dropped,
+    /// at which point the previously installed hook is restored.
+    pub fn install_scoped(self) -> ScopedHook {
+        let previous = Arc::new(panic::take_hook());
+        let chained = if self.config.chain_previous {
+            Some(previous.clone())
+        } else {
+            None
+        };
+        panic::set_hook(self.build_chained(chained));
+        ScopedHook::new(previous)
+    }
+}
+
+/// The settings to override a hook's configuration with: the global ones,
+/// then the list, which is more likely to have been set for this one run.
+fn read_settings() -> Settings {
+    let mut settings = Settings::default();
+    imp::global_settings(&mut settings);
+    if let Some(list) = imp::settings_list() {
+        settings.apply_list(&list);
+    }
+    settings
+}
+
+/// Cut `s` down to at most `max_length` bytes, at a character boundary, and
+/// mark how much was cut.
+fn truncate(s: &mut String, max_length: usize) {
+    if s.len() <= max_length {
+        return;
+    }
+    let mut end = max_length;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    let cut = s.len() - end;
+    s.truncate(end);
+    s.push_str(&format!("… [{} more bytes]", cut));
+}
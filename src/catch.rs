@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+
+use report::PanicReport;
+
+thread_local! {
+    /// While in `catch_and_report` on this thread, the last panic reported.
+    static CAUGHT: RefCell<Option<Option<PanicReport>>> = const { RefCell::new(None) };
+}
+
+/// Whether we are in `catch_and_report` on this thread, and so need a report
+/// of any panic.
+pub(crate) fn is_catching() -> bool {
+    CAUGHT.with(|caught| caught.borrow().is_some())
+}
+
+/// Keep a copy of `report` for `catch_and_report` to return, if we are in it.
+pub(crate) fn keep(report: &PanicReport) {
+    CAUGHT.with(|caught| {
+        if let Some(ref mut caught) = *caught.borrow_mut() {
+            *caught = Some(report.clone());
+        }
+    });
+}
+
+/// Run `f`, returning the report of its panic as an `Err` if it panics,
+/// rather than leaving the caller with a trap.
+///
+/// This is meant for the functions a module exports, so that a panic reaches
+/// JavaScript as a rejected promise or a thrown exception with the panic in
+/// it. On wasm, `PanicReport` converts into a `JsValue` for that:
+///
+/// ```no_run
+/// # fn parse(input: &str) -> usize { input.len() }
+/// # #[cfg(target_arch = "wasm32")]
+/// # fn export(input: &str) -> Result<usize, wasm_bindgen::JsValue> {
+/// console_error_panic_hook::catch_and_report(|| parse(input)).map_err(Into::into)
+/// # }
+/// ```
+///
+/// The panic is still reported as usual. When it was reported by a hook from
+/// this crate, the returned report is the same one its reporters received;
+/// otherwise it only has the payload.
+///
+/// Panics can only be caught where they unwind. Under `panic = "abort"`,
+/// which is the default for wasm, the panic is reported and then aborts as
+/// it always would. Like `std::panic::catch_unwind`, this catches panics
+/// from `f` that leave the state it borrows half-updated, but it doesn't
+/// require `f` to be `UnwindSafe`: check that nothing `f` touches is used
+/// after a panic, unless it can cope with that.
+// Panics are rare, and a boxed report would be one more thing to unwrap.
+#[allow(clippy::result_large_err)]
+pub fn catch_and_report<R>(f: impl FnOnce() -> R) -> Result<R, PanicReport> {
+    let outer = CAUGHT.with(|caught| caught.replace(Some(None)));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    let caught = CAUGHT
+        .with(|caught| caught.replace(outer))
+        .and_then(|caught| caught);
+    result.map_err(|payload| caught.unwrap_or_else(|| PanicReport::from_payload(&*payload)))
+}
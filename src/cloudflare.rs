@@ -0,0 +1,57 @@
+use super::{imp, PanicReport, Reporter};
+
+/// A reporter for Cloudflare Workers, which logs each panic with
+/// `console.error` as a single object, so that Workers Logs indexes its
+/// `message`, `payload`, `file`, `line`, `column`, `thread`, `stack`,
+/// `timestamp`, `uptime`, `memory`, `environment`, `build`, and `moduleName`
+/// as separate fields.
+///
+/// On non-wasm targets there are no objects to log, and this writes the
+/// message to `stderr` like a [`ConsoleReporter`](struct.ConsoleReporter.html).
+///
+/// ```
+/// use console_error_panic_hook::CloudflareReporter;
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(CloudflareReporter::new()))
+///     .install();
+/// ```
+///
+/// The isolate may be recycled right after a panicking request, before any
+/// remote delivery of the panic completes. To send panics elsewhere, also add
+/// a [`BufferReporter`](struct.BufferReporter.html), and hand their delivery
+/// to `waitUntil` when the request is done, which keeps the isolate alive
+/// until it settles. With the `drain` feature, from the Worker's JavaScript:
+///
+/// ```js
+/// export default {
+///   async fetch(request, env, ctx) {
+///     try {
+///       return await handle(request, env);
+///     } finally {
+///       const reports = drainPanicReports();
+///       if (reports.length > 0) {
+///         ctx.waitUntil(fetch(env.PANIC_ENDPOINT, {
+///           method: "POST",
+///           body: JSON.stringify(reports),
+///         }));
+///       }
+///     }
+///   },
+/// };
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CloudflareReporter;
+
+impl CloudflareReporter {
+    /// Create a reporter that logs panics in the Workers Logs format.
+    pub fn new() -> CloudflareReporter {
+        CloudflareReporter
+    }
+}
+
+impl Reporter for CloudflareReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::console_record(report);
+    }
+}
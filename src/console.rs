@@ -0,0 +1,473 @@
+use std::sync::Mutex;
+
+use super::buffer::RingBuffer;
+use super::{imp, lock, report, PanicReport, Reporter};
+
+/// How many undelivered panics are kept, dropping the oldest ones past that.
+const UNDELIVERED_CAPACITY: usize = 32;
+
+/// Panics that could not be logged.
+static UNDELIVERED: Mutex<RingBuffer> = Mutex::new(RingBuffer::new());
+
+/// The default reporter, which logs panics with `console.error`, or to
+/// `stderr` on non-wasm targets and WASI.
+///
+/// Under Deno, the panic's fields are always logged after the message, for
+/// Deno's inspector to print, and with the `color` feature the message is
+/// colored unless `Deno.noColor` is set.
+///
+/// ```
+/// use console_error_panic_hook::ConsoleReporter;
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new().with_object(true)))
+///     .install();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ConsoleReporter {
+    pub(crate) object: bool,
+    pub(crate) error_object: bool,
+    pub(crate) node_stderr: bool,
+    pub(crate) report_error: bool,
+    pub(crate) group: bool,
+    pub(crate) trace: bool,
+    pub(crate) table: bool,
+    pub(crate) styles: Option<Styles>,
+    pub(crate) level: ConsoleLevel,
+}
+
+/// Which `console` method a [`ConsoleReporter`] logs panics with. See
+/// [`ConsoleReporter::with_level`].
+///
+/// [`ConsoleReporter`]: struct.ConsoleReporter.html
+/// [`ConsoleReporter::with_level`]: struct.ConsoleReporter.html#method.with_level
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    /// Log panics with `console.error`.
+    #[default]
+    Error,
+    /// Log panics with `console.warn`.
+    Warn,
+    /// Log panics with `console.log`.
+    Log,
+}
+
+impl ConsoleReporter {
+    /// Create a console reporter with the default settings.
+    pub fn new() -> ConsoleReporter {
+        ConsoleReporter::default()
+    }
+
+    /// Whether to pass an object with the panic's `payload`, `file`, `line`,
+    /// `column`, and `thread` to `console.error` after the message, so
+    /// devtools can expand and filter on them. Defaults to `false`.
+    ///
+    /// This has no effect on non-wasm targets.
+    pub fn with_object(mut self, object: bool) -> ConsoleReporter {
+        self.object = object;
+        self
+    }
+
+    /// Which `console` method to log panics with. Defaults to
+    /// `ConsoleLevel::Error`, for `console.error`.
+    ///
+    /// ```
+    /// use console_error_panic_hook::{ConsoleLevel, ConsoleReporter};
+    ///
+    /// console_error_panic_hook::builder()
+    ///     .with_reporter(Box::new(ConsoleReporter::new().with_level(ConsoleLevel::Warn)))
+    ///     .install();
+    /// ```
+    ///
+    /// This has no effect on non-wasm targets, or on what
+    /// [`with_node_stderr`](#method.with_node_stderr) writes.
+    pub fn with_level(mut self, level: ConsoleLevel) -> ConsoleReporter {
+        self.level = level;
+        self
+    }
+
+    /// Whether to log the message as a JavaScript `Error` (created with
+    /// `new Error(message)`) instead of a plain string. Defaults to `false`.
+    ///
+    /// Browsers render logged `Error`s with their own stack trace, complete
+    /// with source mapping, which tends to be more useful than the stack of
+    /// the `console.error` call. This has no effect on non-wasm targets.
+    pub fn with_error_object(mut self, error_object: bool) -> ConsoleReporter {
+        self.error_object = error_object;
+        self
+    }
+
+    /// Whether, under Node, to write panics straight to `stderr` with a
+    /// synchronous `fs.writeSync` instead of logging them with
+    /// `console.error`. Defaults to `false`.
+    ///
+    /// Node's `console.error` is asynchronous when `stderr` is a pipe, so its
+    /// output can interleave with other output, or be lost if the process
+    /// dies first. This makes sure the panic is written before the module
+    /// traps. It falls back to `console.error` outside of Node, and the other
+    /// options have no effect on what is written. This has no effect on
+    /// non-wasm targets, which always write to `stderr` synchronously.
+    pub fn with_node_stderr(mut self, node_stderr: bool) -> ConsoleReporter {
+        self.node_stderr = node_stderr;
+        self
+    }
+
+    /// Whether to also pass panics to the global `reportError`, as a
+    /// JavaScript `Error` created with `new Error(message)`. Defaults to
+    /// `false`.
+    ///
+    /// `reportError` goes through the page's usual handling of uncaught
+    /// errors, so `error` event listeners and error tracking SDKs see the
+    /// panic too. Browsers print reported errors themselves, so expect the
+    /// panic twice in devtools, and under Deno an unhandled reported error
+    /// ends the process. Where there is no `reportError`, such as in Node,
+    /// the panic is only logged. This has no effect on non-wasm targets.
+    pub fn with_report_error(mut self, report_error: bool) -> ConsoleReporter {
+        self.report_error = report_error;
+        self
+    }
+
+    /// Whether to log panics inside a `console.group("Rust panic: ...")`,
+    /// with each section of the message, such as the location and the stack,
+    /// on its own line. Defaults to `false`.
+    ///
+    /// Where there is no `console.group`, the panic is logged as usual. Under
+    /// Node with [`with_node_stderr`](#method.with_node_stderr), panics are
+    /// still written to `stderr` in one piece. This has no effect on non-wasm
+    /// targets.
+    pub fn with_group(mut self, group: bool) -> ConsoleReporter {
+        self.group = group;
+        self
+    }
+
+    /// Style panics with `%c` directives, as the browsers' consoles support,
+    /// so that they stand out from other logs. Defaults to `None`, for plain
+    /// messages.
+    ///
+    /// ```
+    /// use console_error_panic_hook::{ConsoleReporter, Styles};
+    ///
+    /// let pretty = ConsoleReporter::new().with_styles(Some(Styles::default()));
+    /// let custom = ConsoleReporter::new()
+    ///     .with_styles(Some(Styles::new("color: darkorange", "font-style: italic")));
+    /// ```
+    ///
+    /// Node ignores the styles. This has no effect with
+    /// [`with_error_object`](#method.with_error_object), or on non-wasm
+    /// targets.
+    pub fn with_styles(mut self, styles: Option<Styles>) -> ConsoleReporter {
+        self.styles = styles;
+        self
+    }
+
+    /// Whether to also log the panic's `payload`, `file`, `line`, `column`,
+    /// and `thread` with `console.table`, so that devtools show them as a grid
+    /// of keys and values. Defaults to `false`.
+    ///
+    /// The grid also has a row for each line of the panic's
+    /// [context](fn.context.html), each pair of its
+    /// [state](fn.add_context_provider.html), each
+    /// [breadcrumb](fn.add_breadcrumb.html), and its memory size, if it has
+    /// those.
+    ///
+    /// This has no effect on non-wasm targets.
+    pub fn with_table(mut self, table: bool) -> ConsoleReporter {
+        self.table = table;
+        self
+    }
+
+    /// Whether to also call `console.trace()` after logging a panic, so that
+    /// devtools show a collapsible JavaScript stack for it. Defaults to
+    /// `false`.
+    ///
+    /// This helps where `console.error` doesn't come with a stack, and where
+    /// the hook's verbosity leaves the stack out of the message. This has no
+    /// effect on non-wasm targets.
+    pub fn with_trace(mut self, trace: bool) -> ConsoleReporter {
+        self.trace = trace;
+        self
+    }
+
+    /// Take the panics that could not be logged, oldest first.
+    ///
+    /// Some places, such as worklets and stripped-down JavaScript shells, have
+    /// no `console` at all, or one that throws. Rather than lose panics there,
+    /// console reporters keep the most recent ones for the embedder to fetch
+    /// later and deliver some other way.
+    pub fn take_undelivered() -> Vec<PanicReport> {
+        lock(&UNDELIVERED).drain()
+    }
+}
+
+impl Reporter for ConsoleReporter {
+    fn report(&self, report: &PanicReport) {
+        if !imp::console_report(self, report) {
+            lock(&UNDELIVERED).push(report.clone(), UNDELIVERED_CAPACITY);
+        }
+    }
+}
+
+/// The CSS that [`ConsoleReporter::with_styles`] applies to the parts of a
+/// panic message.
+///
+/// [`ConsoleReporter::with_styles`]: struct.ConsoleReporter.html#method.with_styles
+#[derive(Clone, Debug, PartialEq)]
+pub struct Styles {
+    pub(crate) payload: String,
+    pub(crate) location: String,
+}
+
+impl Styles {
+    /// Style the payload with `payload` and the `file:line:column` location
+    /// with `location`, both given as CSS declarations.
+    pub fn new(payload: &str, location: &str) -> Styles {
+        Styles {
+            payload: payload.to_string(),
+            location: location.to_string(),
+        }
+    }
+}
+
+impl Default for Styles {
+    /// The payload in bold red and the location dimmed.
+    fn default() -> Styles {
+        Styles::new("color: red; font-weight: bold", "opacity: 0.6")
+    }
+}
+
+/// The label of the `console.group` or `console.trace` for `report`.
+//
+// Only the wasm backend groups and traces panics.
+#[allow(dead_code)]
+pub(crate) fn group_label(report: &PanicReport) -> String {
+    let mut label = "Rust panic".to_string();
+    if let Some(module_name) = report.module_name() {
+        label.push_str(" in ");
+        label.push_str(module_name);
+    }
+    if let Some(payload) = report.payload().and_then(|payload| payload.lines().next()) {
+        label.push_str(": ");
+        label.push_str(payload);
+    }
+    label
+}
+
+/// The report's message split into the sections the hook appended to it: the
+/// message itself, then the context, the build, the time, the state, the
+/// breadcrumbs, the memory size, the source link, and the stack, if any.
+#[allow(dead_code)]
+pub(crate) fn sections(report: &PanicReport) -> Vec<String> {
+    let appended = [
+        report::context_section(report),
+        report::build_section(report),
+        report::time_section(report),
+        report::state_section(report),
+        report::breadcrumbs_section(report),
+        report::memory_section(report),
+        report.source_link().map(|link| format!("Source: {}", link)),
+        report.stack().map(|stack| format!("Stack:\n\n{}", stack)),
+    ];
+
+    let mut msg = report.message();
+    let mut sections = Vec::new();
+    for section in appended.iter().rev().flatten() {
+        if let Some(rest) = msg.strip_suffix(&format!("\n\n{}", section)[..]) {
+            sections.push(section.clone());
+            msg = rest;
+        }
+    }
+
+    sections.push(msg.to_string());
+    sections.reverse();
+    sections
+}
+
+/// The parts of a panic message worth highlighting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Part {
+    Plain,
+    Location,
+    Payload,
+}
+
+/// Split `msg`, the message of `report` or its first section, into its
+/// location, its payload, and the plain text around them.
+//
+// Not every backend highlights panics.
+#[allow(dead_code)]
+pub(crate) fn highlight<'a>(msg: &'a str, report: &PanicReport) -> Vec<(Part, &'a str)> {
+    let mut parts = Vec::new();
+    let mut rest = msg;
+
+    if let (Some(file), Some(line), Some(column)) = (report.file(), report.line(), report.column())
+    {
+        let location = format!("{}:{}:{}", file, line, column);
+        if let Some(start) = rest.find(&location) {
+            let end = start + location.len();
+            parts.push((Part::Plain, &rest[..start]));
+            parts.push((Part::Location, &rest[start..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    // The payload comes after the location, so only look for it there.
+    if let Some(payload) = report.payload().filter(|payload| !payload.is_empty()) {
+        if let Some(start) = rest.find(payload) {
+            let end = start + payload.len();
+            parts.push((Part::Plain, &rest[..start]));
+            parts.push((Part::Payload, &rest[start..end]));
+            rest = &rest[end..];
+        }
+    }
+
+    parts.push((Part::Plain, rest));
+    parts.retain(|&(_, text)| !text.is_empty());
+    parts
+}
+
+/// The report's message with its location dimmed and its payload in bold red,
+/// for terminals.
+//
+// Not every backend writes to a terminal.
+#[cfg(feature = "color")]
+#[allow(dead_code)]
+pub(crate) fn colorize(report: &PanicReport) -> String {
+    const DIM: &str = "\x1b[2m";
+    const BOLD_RED: &str = "\x1b[1;31m";
+    const RESET: &str = "\x1b[0m";
+
+    let msg = report.message();
+    let mut colored = String::with_capacity(msg.len() + 32);
+    for (part, text) in highlight(msg, report) {
+        match part {
+            Part::Plain => colored.push_str(text),
+            Part::Location => {
+                colored.push_str(DIM);
+                colored.push_str(text);
+                colored.push_str(RESET);
+            }
+            Part::Payload => {
+                colored.push_str(BOLD_RED);
+                colored.push_str(text);
+                colored.push_str(RESET);
+            }
+        }
+    }
+    colored
+}
+
+/// `msg` as a `console` format string with a `%c` directive before each part,
+/// along with the CSS for each directive.
+#[allow(dead_code)]
+pub(crate) fn stylize(msg: &str, report: &PanicReport, styles: &Styles) -> (String, Vec<String>) {
+    let mut format = String::with_capacity(msg.len() + 32);
+    let mut css = Vec::new();
+    for (part, text) in highlight(msg, report) {
+        format.push_str("%c");
+        // Anything else that looks like a directive would eat the styles.
+        format.push_str(&text.replace('%', "%%"));
+        css.push(match part {
+            Part::Plain => String::new(),
+            Part::Location => styles.location.clone(),
+            Part::Payload => styles.payload.clone(),
+        });
+    }
+    (format, css)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Breadcrumb, BuildInfo};
+
+    /// A bare panic at `src/main.rs:3:5`, for each test to add to.
+    fn report() -> PanicReport {
+        PanicReport {
+            message: "panicked at src/main.rs:3:5:\nboom".to_string(),
+            payload: Some("boom".to_string()),
+            file: Some("src/main.rs".to_string()),
+            line: Some(3),
+            column: Some(5),
+            thread: "main".to_string(),
+            timestamp: None,
+            uptime: None,
+            memory: None,
+            environment: None,
+            build: None,
+            breadcrumbs: Vec::new(),
+            context: Vec::new(),
+            state: Vec::new(),
+            module_name: None,
+            stack: None,
+            source_link: None,
+        }
+    }
+
+    #[test]
+    fn splits_sections() {
+        let linked = PanicReport {
+            message: "panicked at src/main.rs:3:5:\nboom\n\nboom\n\nSource: https://example.com/src/main.rs#L3\n\nStack:\n\nError\n    at main".to_string(),
+            payload: Some("boom\n\nboom".to_string()),
+            stack: Some("Error\n    at main".to_string()),
+            source_link: Some("https://example.com/src/main.rs#L3".to_string()),
+            ..report()
+        };
+        assert_eq!(group_label(&linked), "Rust panic: boom");
+        assert_eq!(
+            sections(&linked),
+            [
+                "panicked at src/main.rs:3:5:\nboom\n\nboom",
+                "Source: https://example.com/src/main.rs#L3",
+                "Stack:\n\nError\n    at main",
+            ]
+        );
+
+        let detailed = PanicReport {
+            message: "panicked at src/main.rs:3:5:\nboom\n\nContext:\n  loading\n\nBuild: 1.0.0\n\nTime: 1.5ms after start\n\nState:\n  user: 7\n\nBreadcrumbs:\n  [ui] clicked\n\nStack:\n\nError\n    at main".to_string(),
+            uptime: Some(1.5),
+            build: Some(BuildInfo::new("1.0.0")),
+            breadcrumbs: vec![Breadcrumb {
+                category: "ui".to_string(),
+                message: "clicked".to_string(),
+                timestamp: None,
+            }],
+            context: vec!["loading".to_string()],
+            state: vec![("user".to_string(), "7".to_string())],
+            stack: Some("Error\n    at main".to_string()),
+            ..report()
+        };
+        assert_eq!(
+            sections(&detailed),
+            [
+                "panicked at src/main.rs:3:5:\nboom",
+                "Context:\n  loading",
+                "Build: 1.0.0",
+                "Time: 1.5ms after start",
+                "State:\n  user: 7",
+                "Breadcrumbs:\n  [ui] clicked",
+                "Stack:\n\nError\n    at main",
+            ]
+        );
+    }
+
+    #[test]
+    fn styles_payload_and_location() {
+        let report = PanicReport {
+            message: "panicked at src/main.rs:3:5:\n100% boom".to_string(),
+            payload: Some("100% boom".to_string()),
+            ..report()
+        };
+        let (format, css) = stylize(report.message(), &report, &Styles::new("p", "l"));
+        assert_eq!(format, "%cpanicked at %csrc/main.rs:3:5%c:\n%c100%% boom");
+        assert_eq!(css, ["", "l", "", "p"]);
+    }
+
+    #[cfg(feature = "color")]
+    #[test]
+    fn colors_payload_and_location() {
+        assert_eq!(
+            colorize(&report()),
+            "panicked at \x1b[2msrc/main.rs:3:5\x1b[0m:\n\x1b[1;31mboom\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+use super::{imp, lock};
+
+thread_local! {
+    /// What this thread is in the middle of, outermost first.
+    static CONTEXT: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+
+    /// Whether this thread is calling context providers, so that the panic of
+    /// one doesn't call them all over again.
+    static PROVIDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A guard that adds a line of context to any panic on this thread for as
+/// long as it is alive. Create one with [`context`](fn.context.html).
+#[must_use = "the context is removed as soon as the guard is dropped"]
+pub struct ContextGuard {
+    /// How much context there was before ours.
+    depth: usize,
+    /// The context belongs to the thread it was added on.
+    _thread: PhantomData<*const ()>,
+}
+
+impl fmt::Debug for ContextGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ContextGuard").finish()
+    }
+}
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        // Also drops the context of guards that should have gone first.
+        let _ = CONTEXT.try_with(|context| context.borrow_mut().truncate(self.depth));
+    }
+}
+
+/// Say what this thread is doing until the returned guard is dropped, so
+/// that a panic in the meantime says so too.
+///
+/// The context of every live guard on the panicking thread goes in its
+/// report, outermost first, as a "Context:" section of its message and as
+/// its [`context`](struct.PanicReport.html#method.context). This is a lot
+/// less work than returning `Result`s all the way up just for diagnostics.
+///
+/// ```
+/// # fn decode(_: &str) {}
+/// let name = "save-1.dat";
+/// let _context = console_error_panic_hook::context(format!("decoding {}", name));
+/// decode(name);
+/// ```
+pub fn context<S: Into<String>>(context: S) -> ContextGuard {
+    let context = context.into();
+    let depth = CONTEXT.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        stack.push(context);
+        stack.len() - 1
+    });
+    ContextGuard {
+        depth,
+        _thread: PhantomData,
+    }
+}
+
+/// A function that describes the application's state, as key-value pairs,
+/// when a panic happens. See
+/// [`add_context_provider`](fn.add_context_provider.html).
+pub type ContextProvider = fn() -> Vec<(String, String)>;
+
+static PROVIDERS: Mutex<Vec<ContextProvider>> = Mutex::new(Vec::new());
+
+/// Call `provider` whenever a panic is reported, to add what it returns to
+/// the report, as a "State:" section of its message and as its
+/// [`state`](struct.PanicReport.html#method.state).
+///
+/// This suits state that changes too often to keep a
+/// [`context`](fn.context.html) guard or breadcrumb for, such as the current
+/// route, entity counts, or feature flags:
+///
+/// ```
+/// fn route() -> Vec<(String, String)> {
+///     vec![("route".to_string(), "/settings".to_string())]
+/// }
+///
+/// console_error_panic_hook::add_context_provider(route);
+/// ```
+///
+/// Providers run while the panic is being reported, when whatever panicked
+/// may have left the application's state borrowed or half-updated. A panic
+/// in a provider would normally abort there and then, so each provider is
+/// called on its own: through JavaScript on wasm, where the trap it ends in
+/// becomes an exception that is caught, and on a thread of its own
+/// elsewhere. There, a provider sees the thread-local state of that thread
+/// rather than of the panicking one, so keep what it reads in `static`s.
+/// The panic of a provider is not reported or counted as a panic of its
+/// own, and doesn't abort, throw, or reach a chained previous hook either;
+/// the report goes on with a "context provider N: panicked" pair in
+/// place of the Nth provider's state. Without JavaScript bindings, or
+/// threads to spawn, there is no way to contain it.
+pub fn add_context_provider(provider: ContextProvider) {
+    lock(&PROVIDERS).push(provider);
+}
+
+/// Stop calling every provider added so far.
+pub fn clear_context_providers() {
+    lock(&PROVIDERS).clear();
+}
+
+/// Whether there are any providers to call.
+pub(crate) fn has_providers() -> bool {
+    !lock(&PROVIDERS).is_empty()
+}
+
+/// What every provider returns, in the order they were added, with a
+/// "context provider N: panicked" pair in place of what the Nth provider
+/// would have returned if it panics.
+pub(crate) fn provided() -> Vec<(String, String)> {
+    if PROVIDING.with(|providing| providing.replace(true)) {
+        return Vec::new();
+    }
+    // A provider may add another, so don't hold the lock while calling them.
+    let providers = lock(&PROVIDERS).clone();
+    let state = providers
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, provider)| {
+            let provided = imp::call_isolated(move || {
+                PROVIDING.with(|providing| providing.set(true));
+                provider()
+            });
+            provided.unwrap_or_else(|| {
+                vec![(
+                    format!("context provider {}", i + 1),
+                    "panicked".to_string(),
+                )]
+            })
+        })
+        .collect();
+    PROVIDING.with(|providing| providing.set(false));
+    state
+}
+
+/// Whether this thread has any context to report.
+pub(crate) fn any() -> bool {
+    CONTEXT
+        .try_with(|context| !context.borrow().is_empty())
+        .unwrap_or(false)
+}
+
+/// A copy of this thread's context, outermost first.
+pub(crate) fn current() -> Vec<String> {
+    CONTEXT
+        .try_with(|context| context.borrow().clone())
+        .unwrap_or_default()
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use addr2line::gimli::{self, EndianSlice, LittleEndian, SectionId};
+use addr2line::Context;
+
+use module;
+
+/// DWARF debugging information from a WebAssembly module, used to add Rust
+/// source locations to the wasm frames of captured stacks.
+///
+/// This only helps for modules built with debug info, such as
+/// `wasm-pack build --dev` output, and requires the `dwarf` feature.
+///
+/// ```no_run
+/// use console_error_panic_hook::DebugInfo;
+///
+/// # let module_bytes: Vec<u8> = vec![];
+/// if let Some(debug_info) = DebugInfo::from_module(&module_bytes) {
+///     console_error_panic_hook::builder()
+///         .with_debug_info(debug_info)
+///         .install();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DebugInfo {
+    /// Where the code section's contents start in the module. DWARF
+    /// addresses for wasm are relative to this, while engines report frame
+    /// offsets relative to the start of the module.
+    code_offset: usize,
+    sections: HashMap<String, Vec<u8>>,
+}
+
+impl DebugInfo {
+    /// Read the `.debug_*` custom sections from the given WebAssembly
+    /// module's bytes.
+    ///
+    /// Returns `None` if `module` isn't a WebAssembly module or has no DWARF
+    /// sections.
+    pub fn from_module(module: &[u8]) -> Option<DebugInfo> {
+        let sections = module::sections(module)?;
+        let code_offset = sections
+            .iter()
+            .find(|section| section.id == module::CODE)?
+            .offset;
+        let sections: HashMap<_, _> = sections
+            .iter()
+            .filter_map(|section| {
+                let name = section.name.filter(|name| name.starts_with(".debug_"))?;
+                Some((name.to_string(), section.payload.to_vec()))
+            })
+            .collect();
+        if sections.is_empty() {
+            return None;
+        }
+        Some(DebugInfo {
+            code_offset,
+            sections,
+        })
+    }
+
+    fn context(&self) -> Option<Context<EndianSlice<'_, LittleEndian>>> {
+        let dwarf = gimli::Dwarf::load(|id: SectionId| -> Result<_, gimli::Error> {
+            let data = self
+                .sections
+                .get(id.name())
+                .map_or(&[][..], |data| &data[..]);
+            Ok(EndianSlice::new(data, LittleEndian))
+        })
+        .ok()?;
+        Context::from_dwarf(dwarf).ok()
+    }
+
+    /// A resolver for the source locations of module offsets, or `None` if
+    /// the DWARF couldn't be parsed.
+    pub(crate) fn resolver(&self) -> Option<Resolver<'_>> {
+        Some(Resolver {
+            code_offset: self.code_offset as u64,
+            context: self.context()?,
+        })
+    }
+}
+
+pub(crate) struct Resolver<'a> {
+    code_offset: u64,
+    context: Context<EndianSlice<'a, LittleEndian>>,
+}
+
+impl<'a> Resolver<'a> {
+    /// The `file:line:column` of the code at `offset` from the start of the
+    /// module, if it is known.
+    pub(crate) fn location(&self, offset: u64) -> Option<String> {
+        let address = offset.checked_sub(self.code_offset)?;
+        let location = self.context.find_location(address).ok()??;
+        let mut resolved = location.file?.to_string();
+        if let Some(line) = location.line {
+            resolved.push_str(&format!(":{}", line));
+            if let Some(column) = location.column {
+                resolved.push_str(&format!(":{}", column));
+            }
+        }
+        Some(resolved)
+    }
+}
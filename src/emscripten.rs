@@ -0,0 +1,17 @@
+//! Logging through Emscripten's console bridge.
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+extern "C" {
+    fn emscripten_console_error(msg: *const c_char);
+}
+
+pub(crate) fn console_error(msg: &str) {
+    // A C string ends at the first NUL, so drop any from the message rather
+    // than lose what comes after them.
+    let msg = CString::new(msg.replace('\0', "")).unwrap_or_default();
+    unsafe {
+        emscripten_console_error(msg.as_ptr());
+    }
+}
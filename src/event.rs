@@ -0,0 +1,46 @@
+use super::{imp, PanicReport, Reporter};
+
+/// A reporter that dispatches each panic as a `rust-panic` `CustomEvent` on
+/// the global object, so that page JavaScript can react to it, say by
+/// offering to reload.
+///
+/// The event's `detail` holds the `message`, `payload`, `file`, `line`,
+/// `column`, `thread`, `stack`, `timestamp`, `uptime`, `memory`,
+/// `environment`, `build`, and `moduleName` of the
+/// [`PanicReport`](struct.PanicReport.html):
+///
+/// ```js
+/// addEventListener("rust-panic", ({ detail }) => {
+///   showCrashBanner(detail.message);
+/// });
+/// ```
+///
+/// Listeners run synchronously, while the panic is being reported, and before
+/// the module traps. Where the global object isn't an `EventTarget`, as in
+/// Node, and on non-wasm targets, this does nothing, so add a
+/// [`ConsoleReporter`](struct.ConsoleReporter.html) too to keep logging
+/// panics:
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, EventReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(EventReporter::new()))
+///     .install();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EventReporter;
+
+impl EventReporter {
+    /// Create a reporter that dispatches panics as `rust-panic` events.
+    pub fn new() -> EventReporter {
+        EventReporter
+    }
+}
+
+impl Reporter for EventReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::dispatch_panic_event(report);
+    }
+}
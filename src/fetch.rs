@@ -0,0 +1,105 @@
+use super::{imp, transport, PanicReport, Reporter, Transport};
+
+/// The largest body sent unless configured otherwise, which is also as much
+/// as browsers allow to be in flight in `keepalive` requests.
+const DEFAULT_MAX_BODY: usize = 64 * 1024;
+
+/// A reporter that `POST`s panics to a remote endpoint with `fetch`, using
+/// `keepalive` so the request outlives the page, for endpoints that need
+/// headers such as auth tokens, which
+/// [`BeaconReporter`](struct.BeaconReporter.html)s can't send.
+///
+/// Each panic is sent as an `application/json` object with the `message`,
+/// `payload`, `file`, `line`, `column`, `thread`, `stack`, `timestamp`,
+/// `uptime`, `memory`, `environment`, `build`, and `moduleName` of the
+/// [`PanicReport`](struct.PanicReport.html). Bodies larger than
+/// [`with_max_body`](#method.with_max_body), 64KiB by default, are sent
+/// without the `stack`, and not at all if they are still too large. Failed
+/// requests are ignored. Where there is no `fetch`, and on non-wasm targets,
+/// this does nothing.
+///
+/// Used as a [`Transport`](trait.Transport.html), panics are sent as an array
+/// of such objects, even when there is only one in a batch. Only failing to
+/// make the request at all counts as a failed delivery, as a `keepalive`
+/// request's outcome may never be known.
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, FetchReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(
+///         FetchReporter::new("https://example.com/panics")
+///             .with_header("Authorization", "Bearer 1234"),
+///     ))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FetchReporter {
+    url: String,
+    headers: Vec<(String, String)>,
+    max_body: usize,
+}
+
+impl FetchReporter {
+    /// Create a reporter that sends panics to `url`.
+    pub fn new<S: Into<String>>(url: S) -> FetchReporter {
+        FetchReporter {
+            url: url.into(),
+            headers: Vec::new(),
+            max_body: DEFAULT_MAX_BODY,
+        }
+    }
+
+    /// Send the header `name` with each request.
+    pub fn with_header<N, V>(mut self, name: N, value: V) -> FetchReporter
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send bodies of at most `max_body` bytes. Defaults to 64KiB.
+    pub fn with_max_body(mut self, max_body: usize) -> FetchReporter {
+        self.max_body = max_body;
+        self
+    }
+
+    /// The body `to_json` makes with the stack if that fits, or without it
+    /// if that does.
+    fn body<F>(&self, to_json: F) -> Option<String>
+    where
+        F: Fn(bool) -> Option<String>,
+    {
+        let body = to_json(true)?;
+        if body.len() <= self.max_body {
+            return Some(body);
+        }
+        to_json(false).filter(|body| body.len() <= self.max_body)
+    }
+}
+
+impl Transport for FetchReporter {
+    fn serialize(&self, reports: &[PanicReport]) -> Option<String> {
+        self.body(|with_stack| imp::to_json(reports, with_stack))
+    }
+
+    fn deliver(&self, payload: &str) -> bool {
+        imp::post(&self.url, "application/json", &self.headers, payload)
+    }
+}
+
+impl Reporter for FetchReporter {
+    fn report(&self, report: &PanicReport) {
+        transport::send(
+            self,
+            self.body(|with_stack| imp::report_to_json(report, with_stack)),
+        );
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
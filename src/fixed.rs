@@ -0,0 +1,45 @@
+//! A fixed-size buffer for formatting messages without allocating.
+
+// This is built with and without `std`, so it names `core` in full rather
+// than importing from it.
+
+/// How much of a message fits. Anything past this is cut off.
+const CAPACITY: usize = 1024;
+
+/// A fixed-size buffer that drops whatever doesn't fit, failing the write
+/// that didn't.
+pub(crate) struct Buffer {
+    bytes: [u8; CAPACITY],
+    len: usize,
+}
+
+impl Buffer {
+    pub(crate) const fn new() -> Buffer {
+        Buffer {
+            bytes: [0; CAPACITY],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        // Only whole `str`s and the leading characters of one are ever copied
+        // in, so this never fails.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl core::fmt::Write for Buffer {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut end = s.len().min(CAPACITY - self.len);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.bytes[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+        self.len += end;
+        if end == s.len() {
+            Ok(())
+        } else {
+            Err(core::fmt::Error)
+        }
+    }
+}
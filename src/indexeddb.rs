@@ -0,0 +1,82 @@
+use super::{imp, PanicReport, Reporter};
+
+/// How many panics are kept unless configured otherwise.
+const DEFAULT_CAPACITY: usize = 32;
+
+/// A reporter that appends panics to an IndexedDB object store, where they
+/// survive the page being closed, for post-mortem debugging of apps that
+/// crash in the field. Requires the `indexeddb` feature.
+///
+/// Only the most recent panics are kept, 32 of them unless set with
+/// [`with_capacity`](#method.with_capacity). Fetch them later with
+/// [`stored`](#method.stored), or from JavaScript with `storedPanicReports()`,
+/// which resolves to plain objects with the `message`, `payload`, `file`,
+/// `line`, `column`, `thread`, `stack`, `timestamp`, `uptime`, `memory`,
+/// `environment`, `build`, and `moduleName` of each
+/// [`PanicReport`](struct.PanicReport.html), oldest first:
+///
+/// ```js
+/// const reports = await storedPanicReports();
+/// if (reports.length > 0) {
+///   await fetch("/crash-reports", { method: "POST", body: JSON.stringify(reports) });
+///   await clearPanicReports();
+/// }
+/// ```
+///
+/// Panics are written once the module has trapped, as IndexedDB is
+/// asynchronous. Where there is no IndexedDB, and on non-wasm targets, this
+/// does nothing.
+///
+/// This uses a JavaScript snippet, which `wasm-bindgen`'s `no-modules` target
+/// doesn't support.
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, IndexedDbReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(IndexedDbReporter::new()))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct IndexedDbReporter {
+    capacity: usize,
+}
+
+impl IndexedDbReporter {
+    /// Create a reporter that keeps the 32 most recent panics.
+    pub fn new() -> IndexedDbReporter {
+        IndexedDbReporter::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a reporter that keeps the `capacity` most recent panics.
+    pub fn with_capacity(capacity: usize) -> IndexedDbReporter {
+        IndexedDbReporter { capacity }
+    }
+
+    /// Call `f` with the stored panics, oldest first, once they have been
+    /// read. If they can't be read, `f` gets none.
+    pub fn stored<F>(f: F)
+    where
+        F: FnOnce(Vec<PanicReport>) + 'static,
+    {
+        imp::stored_in_indexeddb(Box::new(f));
+    }
+
+    /// Remove the stored panics.
+    pub fn clear() {
+        imp::clear_indexeddb();
+    }
+}
+
+impl Default for IndexedDbReporter {
+    fn default() -> IndexedDbReporter {
+        IndexedDbReporter::new()
+    }
+}
+
+impl Reporter for IndexedDbReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::append_to_indexeddb(report, self.capacity);
+    }
+}
@@ -65,88 +65,851 @@
 //! }
 //! ```
 //!
+//! ## Configuration
+//!
+//! If the defaults don't suit you, use `console_error_panic_hook::builder()` to
+//! configure the hook before installing it:
+//!
+//! ```
+//! extern crate console_error_panic_hook;
+//!
+//! use console_error_panic_hook::Verbosity;
+//!
+//! fn my_init_function() {
+//!     console_error_panic_hook::builder()
+//!         .with_prefix("[myapp]")
+//!         .with_verbosity(Verbosity::Short)
+//!         .install();
+//! }
+//! ```
+//!
+//! ## Threads
+//!
+//! The hook is shared by every thread, including the workers of a wasm module
+//! built with `-C target-feature=+atomics`: installing it from any one of them
+//! is enough, and `set_once` only ever installs it once. Panics on threads
+//! other than the main one name the thread in their message, and
+//! `PanicReport::thread` tells which one it was.
+//!
+//! ## JavaScript callback
+//!
+//! If there is a global `__onRustPanic` function, the hook also calls it with
+//! each panic, after the reporters have run. It gets the same object as
+//! `WorkerReporter` posts, so JavaScript can route panics elsewhere without
+//! any changes on the Rust side:
+//!
+//! ```js
+//! globalThis.__onRustPanic = (report) => {
+//!   errorTracker.capture(report.message, { extra: report });
+//! };
+//! ```
+//!
+//! Anything the callback throws is ignored.
+//!
+//! ## Cargo features
+//!
+//! * `alloc-error-hook`: provide `set_alloc_error_hook`, which reports failed
+//!   memory allocations as panics, so that they reach the panic hook instead
+//!   of trapping without a word. The standard library only lets the
+//!   allocation error hook be set on nightly toolchains; elsewhere this does
+//!   nothing yet.
+//! * `auto-install`: on wasm, call `set_once` from a `#[wasm_bindgen(start)]`
+//!   function, so that the hook is set as soon as the module is instantiated,
+//!   before anything can panic, without any code in the application to do it.
+//!   A module can only have the one start function, so this doesn't go along
+//!   with an application's own `#[wasm_bindgen(start)]`; call `set_once` from
+//!   that instead.
+//! * `color`: on non-wasm targets, color panics written to a terminal, with
+//!   the payload in red and the location dimmed. Set `NO_COLOR` to opt out.
+//!   Under Deno, color them on the console unless `Deno.noColor` is set.
+//! * `console-log`: provide `init_with_logging`, which installs the panic hook
+//!   along with a `console_log` logger, so that `log` records go to the
+//!   console as well.
+//! * `disabled`: turn `hook`, the `set_once` functions, `init_with_logging`
+//!   and `set_alloc_error_hook` into empty functions, so that a library can
+//!   call them unconditionally and leave it to the final application to
+//!   compile them away, along with all of their `wasm-bindgen` imports.
+//!   Hooks built with `builder()` are unaffected.
+//! * `demangle`: rewrite mangled Rust symbols in captured stack traces into
+//!   their readable form.
+//! * `dwarf`: resolve the wasm frames of captured stacks to Rust source
+//!   locations using a module's DWARF debugging information, with
+//!   `DebugInfo`.
+//! * `drain`: on wasm, export a `drainPanicReports()` JavaScript function
+//!   returning the panics kept by `BufferReporter`s as plain objects.
+//! * `gloo-console`: on wasm, log panics with `gloo_console::error!` instead
+//!   of this crate's own `console.error` bindings. Unlike those, it doesn't
+//!   catch exceptions, so where `console.error` throws, panics are not kept
+//!   for `ConsoleReporter::take_undelivered`.
+//! * `indexeddb`: keep panics in IndexedDB with `IndexedDbReporter`, and on
+//!   wasm, export `storedPanicReports()` and `clearPanicReports()` JavaScript
+//!   functions to fetch and remove them.
+//! * `json`: serialize panic reports as JSON with `PanicReport::to_json`, and
+//!   log them as JSON lines with `JsonReporter`.
+//! * `listeners`: on wasm, export `addPanicListener(listener)` and
+//!   `removePanicListener(listener)` JavaScript functions. The hook calls each
+//!   listener with the same object as it passes to `__onRustPanic`, on the
+//!   thread that panicked, which is the only one it was added on.
+//! * `log`: log panics at `error` level, with the target `panic`, through the
+//!   `log` crate with `LogReporter`.
+//! * `macros`: provide the `#[console_error_panic_hook::main]` attribute,
+//!   which sets the hook with `set_once` at the start of the function it is
+//!   put on. See [`main`](attr.main.html).
+//! * `minimal`: make `hook` and `set_once` log only the panic's payload and
+//!   location, pieced together without `core::fmt` and without a stack, so
+//!   they add as little code as possible to release builds. `builder()` and
+//!   the other `set_once_with*` functions keep working as usual, and pull in
+//!   the rest of the crate.
+//! * `otel`: emit panics as OpenTelemetry log records with `OtelReporter`,
+//!   through a logger from the application's own provider and exporter.
+//! * `panic-count`: on wasm, export a `panicCount()` JavaScript function
+//!   returning `panic_count()`, so that the page can tell that the module has
+//!   panicked and stop calling into it.
+//! * `panic-handler`: for `no_std` wasm modules, which can't install a panic
+//!   hook, provide a `#[panic_handler]` that logs panics with `console.error`
+//!   instead. It only takes effect with the `std` feature disabled. Unless the
+//!   `wasm-bindgen` feature is disabled as well, `wasm-bindgen` still needs a
+//!   `#[global_allocator]`.
+//! * `regex`: redact text matching regular expressions from panic reports,
+//!   with `HookBuilder::with_redaction_pattern`.
+//! * `sentry`: send panics to Sentry, or anything else speaking its protocol
+//!   such as GlitchTip, with `SentryReporter`.
+//! * `tracing`: emit panics as `tracing` events at `ERROR` level, with the
+//!   target `panic`, with `TracingReporter`.
+//! * `wasi-logging`: on WASI 0.2 components such as `wasm32-wasip2` ones, log
+//!   panics at `error` level through the `wasi:logging/logging` interface
+//!   instead of writing them to `stderr`.
+//! * `wasm-bindgen` (enabled by default): log through `wasm-bindgen`'s
+//!   bindings. See below for what happens without it.
+//! * `std` (enabled by default): the panic hook and everything around it.
+//!   Without it the crate is `#![no_std]`.
+//!
+//! ## WASI
+//!
+//! On WASI targets such as `wasm32-wasip1`, there is no JavaScript to call:
+//! panics are written to WASI's `stderr` instead, as they are on native
+//! targets. To write them to a preopened file instead, use a `WriterReporter`:
+//!
+//! ```no_run
+//! extern crate console_error_panic_hook;
+//!
+//! use console_error_panic_hook::WriterReporter;
+//! use std::fs::File;
+//!
+//! fn my_init_function() {
+//!     let log = File::create("/logs/panics.log").unwrap();
+//!     console_error_panic_hook::builder()
+//!         .with_reporter(Box::new(WriterReporter::new(Box::new(log))))
+//!         .install();
+//! }
+//! ```
+//!
+//! ## Emscripten
+//!
+//! On `wasm32-unknown-emscripten`, panics are logged with Emscripten's own
+//! `emscripten_console_error`, rather than through `wasm-bindgen`.
+//!
+//! ## Without `wasm-bindgen`
+//!
+//! Disabling the default `wasm-bindgen` feature drops that dependency, and
+//! panics are logged through a plain wasm import instead: the function `error`
+//! in the `console_error_panic_hook` import module, which receives a pointer
+//! to and the length of the UTF-8 message. In that mode no stack is captured,
+//! and `ConsoleReporter`'s options have no effect. Provide the import when
+//! instantiating the module:
+//!
+//! ```js
+//! const { instance } = await WebAssembly.instantiate(bytes, {
+//!   console_error_panic_hook: {
+//!     error(ptr, len) {
+//!       // On wasm64, `ptr` and `len` are `BigInt`s.
+//!       const memory = new Uint8Array(instance.exports.memory.buffer, Number(ptr), Number(len));
+//!       console.error(new TextDecoder().decode(memory));
+//!     },
+//!   },
+//! });
+//! ```
+//!
 //! ## Error.stackTraceLimit
 //!
 //! Many browsers only capture the top 10 frames of a stack trace. In rust programs this is less likely to be enough. To see more frames, you can set the non-standard value `Error.stackTraceLimit`. For more information see the [MDN Web Docs](https://developer.mozilla.org/en-US/docs/Web/JavaScript/Microsoft_Extensions/Error.stackTraceLimit) or [v8 docs](https://v8.dev/docs/stack-trace-api).
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
+// The wasm64 intrinsics are still unstable, and the panic handler needs
+// `unreachable`. The target needs a nightly toolchain anyway.
+#![cfg_attr(
+    all(
+        target_arch = "wasm64",
+        feature = "panic-handler",
+        not(feature = "std")
+    ),
+    feature(simd_wasm64)
+)]
+#![cfg_attr(has_alloc_error_hook, feature(alloc_error_hook))]
+
+#[cfg(feature = "dwarf")]
+extern crate addr2line;
 #[macro_use]
 extern crate cfg_if;
+#[cfg(feature = "macros")]
+extern crate console_error_panic_hook_macros;
+#[cfg(feature = "console-log")]
+extern crate console_log;
+#[cfg(feature = "gloo-console")]
+extern crate gloo_console;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "otel")]
+extern crate opentelemetry;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "demangle")]
+extern crate rustc_demangle;
+#[cfg(feature = "json")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(all(test, target_arch = "wasm32"))]
+extern crate wasm_bindgen_test;
 
+#[cfg(feature = "std")]
+use std::cell::Cell;
+#[cfg(feature = "std")]
 use std::panic;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+#[cfg(all(feature = "std", not(feature = "disabled")))]
+use std::sync::Once;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(feature = "std")]
+mod beacon;
+#[cfg(feature = "std")]
+mod breadcrumbs;
+#[cfg(feature = "std")]
+mod broadcast;
+#[cfg(feature = "std")]
+mod buffer;
+#[cfg(feature = "std")]
+mod builder;
+#[cfg(feature = "std")]
+mod catch;
+#[cfg(feature = "std")]
+mod cloudflare;
+#[cfg(feature = "std")]
+mod console;
+#[cfg(feature = "std")]
+mod context;
+#[cfg(feature = "dwarf")]
+mod dwarf;
+#[cfg(feature = "std")]
+mod event;
+#[cfg(feature = "std")]
+mod fetch;
+#[cfg(feature = "std")]
+mod fixed;
+#[cfg(feature = "indexeddb")]
+mod indexeddb;
+#[cfg(feature = "log")]
+mod logging;
+#[cfg(all(feature = "minimal", not(feature = "disabled")))]
+mod minimal;
+#[cfg(feature = "std")]
+mod module;
+#[cfg(feature = "std")]
+mod names;
+#[cfg(has_alloc_error_hook)]
+mod oom;
+#[cfg(feature = "otel")]
+mod otel;
+#[cfg(feature = "std")]
+mod parent;
+#[cfg(feature = "std")]
+mod performance;
+#[cfg(feature = "std")]
+mod redact;
+#[cfg(feature = "std")]
+mod report;
+#[cfg(feature = "std")]
+mod runtime;
+#[cfg(feature = "std")]
+mod scoped;
+#[cfg(feature = "sentry")]
+mod sentry;
+#[cfg(feature = "std")]
+mod settings;
+#[cfg(feature = "std")]
+mod stack;
+#[cfg(feature = "std")]
+mod storage;
+#[cfg(feature = "tracing")]
+mod tracing_events;
+#[cfg(feature = "std")]
+mod transport;
+#[cfg(feature = "std")]
+mod websocket;
+#[cfg(feature = "std")]
+mod worker;
+
+#[cfg(feature = "std")]
+pub use beacon::BeaconReporter;
+#[cfg(feature = "std")]
+pub use breadcrumbs::{add_breadcrumb, clear_breadcrumbs, set_max_breadcrumbs, Breadcrumb};
+#[cfg(feature = "std")]
+pub use broadcast::BroadcastReporter;
+#[cfg(feature = "std")]
+pub use buffer::BufferReporter;
+#[cfg(feature = "std")]
+pub use builder::{Formatter, HookBuilder, NodeExit, Verbosity};
+#[cfg(feature = "std")]
+pub use catch::catch_and_report;
+#[cfg(feature = "std")]
+pub use cloudflare::CloudflareReporter;
+#[cfg(feature = "std")]
+pub use console::{ConsoleLevel, ConsoleReporter, Styles};
+#[cfg(feature = "std")]
+pub use context::{
+    add_context_provider, clear_context_providers, context, ContextGuard, ContextProvider,
+};
+#[cfg(feature = "dwarf")]
+pub use dwarf::DebugInfo;
+#[cfg(feature = "std")]
+pub use event::EventReporter;
+#[cfg(feature = "std")]
+pub use fetch::FetchReporter;
+#[cfg(feature = "indexeddb")]
+pub use indexeddb::IndexedDbReporter;
+#[cfg(feature = "log")]
+pub use logging::LogReporter;
+#[cfg(feature = "std")]
+pub use names::FunctionNames;
+#[cfg(feature = "otel")]
+pub use otel::OtelReporter;
+#[cfg(feature = "std")]
+pub use parent::ParentReporter;
+#[cfg(feature = "std")]
+pub use performance::PerformanceReporter;
+#[cfg(feature = "json")]
+pub use report::JsonReporter;
+#[cfg(feature = "std")]
+pub use report::{BuildInfo, Environment, FnReporter, PanicReport, Reporter, WriterReporter};
+#[cfg(feature = "std")]
+pub use runtime::Runtime;
+#[cfg(feature = "std")]
+pub use scoped::ScopedHook;
+#[cfg(feature = "sentry")]
+pub use sentry::SentryReporter;
+#[cfg(feature = "std")]
+pub use storage::StorageReporter;
+#[cfg(feature = "tracing")]
+pub use tracing_events::TracingReporter;
+#[cfg(feature = "std")]
+pub use transport::{Transport, TransportReporter};
+#[cfg(feature = "std")]
+pub use websocket::WebSocketReporter;
+#[cfg(feature = "std")]
+pub use worker::WorkerReporter;
+
+/// Put this on an entry point, such as a `#[wasm_bindgen(start)]` function,
+/// or any exported function, to set the panic hook before its body runs:
+///
+/// ```
+/// #[console_error_panic_hook::main]
+/// fn start() {
+///     // ...
+/// }
+/// ```
+///
+/// This stands in for the `set_panic_hook` function that templates copy
+/// around. Put it above `#[wasm_bindgen]`.
+#[cfg(feature = "macros")]
+pub use console_error_panic_hook_macros::main;
+
+#[cfg(feature = "std")]
+use builder::Config;
+#[cfg(feature = "std")]
+use fixed::Buffer;
+#[cfg(feature = "std")]
+use settings::Settings;
+
+/// The argument to panic hooks: `std::panic::PanicHookInfo` on Rust 1.81 and
+/// later, and `std::panic::PanicInfo` before that.
+///
+/// Name the type through this alias in formatters and custom hooks to support
+/// both without deprecation warnings.
+#[cfg(all(feature = "std", has_panic_hook_info))]
+pub type PanicHookInfo<'a> = panic::PanicHookInfo<'a>;
+
+/// The argument to panic hooks: `std::panic::PanicHookInfo` on Rust 1.81 and
+/// later, and `std::panic::PanicInfo` before that.
+///
+/// Name the type through this alias in formatters and custom hooks to support
+/// both without deprecation warnings.
+#[cfg(all(feature = "std", not(has_panic_hook_info)))]
+pub type PanicHookInfo<'a> = panic::PanicInfo<'a>;
+
+#[cfg(feature = "std")]
+type Hook = Box<dyn Fn(&PanicHookInfo) + Send + Sync + 'static>;
+
+#[cfg(feature = "std")]
+/// The hook that was installed before ours, if we are installed.
+static PREVIOUS_HOOK: Mutex<Option<Arc<Hook>>> = Mutex::new(None);
+
+#[cfg(feature = "std")]
+/// Whether installed hooks should report panics at all.
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+#[cfg(feature = "std")]
+/// How many panics our hooks have seen, for `panic_count`.
+static PANIC_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[cfg(feature = "std")]
+/// The last panic reported, for `last_panic`.
+static LAST_PANIC: Mutex<Option<PanicReport>> = Mutex::new(None);
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// Whether this thread is in the middle of reporting a panic.
+    static REPORTING: Cell<bool> = const { Cell::new(false) };
+
+    /// Whether this thread only exists to help report a panic, so that its
+    /// own panics are ignored.
+    static SUPPRESSED: Cell<bool> = const { Cell::new(false) };
+}
+
+#[cfg(feature = "std")]
+/// What `hook_impl` made of a panic.
+// Only ever returned right away to a hook, so the size doesn't matter.
+#[allow(clippy::large_enum_variant)]
+enum Outcome {
+    /// It was reported, like so.
+    Reported(PanicReport),
+    /// It was left unreported, because reporting is disabled or it was
+    /// filtered out.
+    Unreported,
+    /// It happened while reporting another panic, or on a thread helping to,
+    /// so nothing else is to be done about it.
+    Nested,
+}
+
+#[cfg(feature = "std")]
+/// What is logged when a panic happens while reporting another one. Formatting,
+/// allocating or a reporter may be what panicked, so this involves none of
+/// them.
+const NESTED_PANIC: &str = "panic while reporting panic";
+
+#[cfg(feature = "std")]
+/// Lock `mutex`, ignoring poisoning: a panic while holding one of our locks
+/// must not stop the next panic from being reported.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
 
 cfg_if! {
-    if #[cfg(target_arch = "wasm32")] {
+    if #[cfg(all(target_os = "wasi", feature = "std"))] {
+        // WASI has a real `stderr`, so there is no need for JavaScript.
+        mod native;
+        use native as imp;
+
+        #[cfg(wasi_logging)]
+        extern crate wit_bindgen;
+        #[cfg(wasi_logging)]
+        mod wasi_logging;
+    } else if #[cfg(all(target_os = "emscripten", feature = "std"))] {
+        // Emscripten has its own console bridge, which `wasm-bindgen` doesn't
+        // work with.
+        mod emscripten;
+        mod native;
+        use native as imp;
+    } else if #[cfg(any(target_os = "wasi", target_os = "emscripten"))] {
+        // Without `std` there is nothing to log with.
+    } else if #[cfg(all(target_family = "wasm", feature = "std", feature = "wasm-bindgen"))] {
         extern crate wasm_bindgen;
-        use wasm_bindgen::prelude::*;
 
-        #[wasm_bindgen]
-        extern {
-            #[wasm_bindgen(js_namespace = console)]
-            fn error(msg: String);
+        mod wasm;
+        use wasm as imp;
+    } else if #[cfg(all(target_family = "wasm", feature = "std"))] {
+        mod raw;
+        use raw as imp;
+    } else if #[cfg(all(target_family = "wasm", feature = "panic-handler"))] {
+        #[cfg(feature = "wasm-bindgen")]
+        extern crate wasm_bindgen;
 
-            type Error;
+        #[cfg(not(feature = "wasm-bindgen"))]
+        mod raw;
+        mod fixed;
+        mod panic_handler;
+    } else if #[cfg(feature = "std")] {
+        mod native;
+        use native as imp;
+    }
+}
 
-            #[wasm_bindgen(constructor)]
-            fn new() -> Error;
+#[cfg(feature = "std")]
+/// Report a panic.
+fn hook_impl(config: &Arc<Config>, info: &PanicHookInfo) -> Outcome {
+    if SUPPRESSED.with(|suppressed| suppressed.get()) {
+        return Outcome::Nested;
+    }
+    if REPORTING.with(|reporting| reporting.get()) {
+        imp::log_without_allocating(NESTED_PANIC);
+        return Outcome::Nested;
+    }
+    PANIC_COUNT.fetch_add(1, Ordering::Relaxed);
+    if !ENABLED.load(Ordering::Relaxed) {
+        return Outcome::Unreported;
+    }
+    REPORTING.with(|reporting| reporting.set(true));
+    let _reporting = Reporting;
+    #[cfg(has_alloc_error_hook)]
+    oom::report_failed_allocation(config);
 
-            #[wasm_bindgen(structural, method, getter)]
-            fn stack(error: &Error) -> String;
-        }
+    if config.is_after_first() || config.is_repeat(info) || config.is_rate_limited() {
+        return Outcome::Unreported;
+    }
 
-        fn hook_impl(info: &panic::PanicInfo) {
-            let mut msg = info.to_string();
-
-            // Add the error stack to our message.
-            //
-            // This ensures that even if the `console` implementation doesn't
-            // include stacks for `console.error`, the stack is still available
-            // for the user. Additionally, Firefox's console tries to clean up
-            // stack traces, and ruins Rust symbols in the process
-            // (https://bugzilla.mozilla.org/show_bug.cgi?id=1519569) but since
-            // it only touches the logged message's associated stack, and not
-            // the message's contents, by including the stack in the message
-            // contents we make sure it is available to the user.
-            msg.push_str("\n\nStack:\n\n");
-            let e = Error::new();
-            let stack = e.stack();
-            msg.push_str(&stack);
-
-            // Safari's devtools, on the other hand, _do_ mess with logged
-            // messages' contents, so we attempt to break their heuristics for
-            // doing that by appending some whitespace.
-            // https://github.com/rustwasm/console_error_panic_hook/issues/7
-            msg.push_str("\n\n");
-
-            // Finally, log the panic with `console.error`!
-            error(msg);
-        }
-    } else {
-        use std::io::{self, Write};
+    // With only the message to log, format it on the stack and skip building
+    // a report. If logging it fails, the report keeps it for
+    // `ConsoleReporter::take_undelivered`.
+    let mut buffer = Buffer::new();
+    if config.fast_message(info, &mut buffer)
+        && !imp::has_js_callbacks()
+        && !catch::is_catching()
+        && imp::log_without_allocating(buffer.as_str())
+    {
+        // Only allocate once the message has been logged.
+        let mut report = PanicReport::new(info);
+        report.message = buffer.as_str().to_string();
+        *lock(&LAST_PANIC) = Some(report.clone());
+        return Outcome::Reported(report);
+    }
 
-        fn hook_impl(info: &panic::PanicInfo) {
-            let _ = writeln!(io::stderr(), "{}", info);
-        }
+    let report = config.report(info);
+    catch::keep(&report);
+    // A reporter or JavaScript callback that panics here would abort, or trap
+    // on wasm, with the panic we are reporting lost. Delivering through
+    // `call_isolated` contains that, so that at least the panic itself can
+    // still be logged.
+    let delivering = (config.clone(), report.clone());
+    let delivered = imp::call_isolated(move || {
+        let (config, report) = delivering;
+        deliver(&config, &report);
+    });
+    if delivered.is_none() {
+        log_nested_panic(info);
+    }
+    *lock(&LAST_PANIC) = Some(report.clone());
+    if config.trap_correlation {
+        *lock(&config.last_report) = Some(report.clone());
+    }
+    Outcome::Reported(report)
+}
+
+#[cfg(feature = "std")]
+/// Log that reporting the panic described by `info` panicked, with where the
+/// panic happened and its message, without allocating.
+fn log_nested_panic(info: &PanicHookInfo) {
+    use std::fmt::Write;
+
+    let mut buffer = Buffer::new();
+    let _ = write!(buffer, "{}: {}", NESTED_PANIC, info);
+    imp::log_without_allocating(buffer.as_str());
+}
+
+#[cfg(feature = "std")]
+/// Hand `report` to our reporters, or the runtime's default one, and to any
+/// JavaScript callbacks.
+fn deliver(config: &Config, report: &PanicReport) {
+    if config.reporters.is_empty() {
+        let runtime = config.runtime.unwrap_or_else(Runtime::detect);
+        runtime.default_reporter().report(report);
+    }
+    for reporter in &config.reporters {
+        reporter.report(report);
+    }
+    imp::call_js_callbacks(report);
+}
+
+#[cfg(feature = "std")]
+/// Ignore every later panic on this thread: it is not reported or counted,
+/// and hooks do nothing else about it either, not even call a chained
+/// previous hook. This is for work done on another thread on behalf of the
+/// thread reporting a panic.
+//
+// Only backends with threads to spawn do that.
+#[allow(dead_code)]
+pub(crate) fn suppress_reports() {
+    SUPPRESSED.with(|suppressed| suppressed.set(true));
+}
+
+#[cfg(feature = "std")]
+/// Clears `REPORTING` once a panic has been reported, even if reporting it
+/// unwound.
+struct Reporting;
+
+#[cfg(feature = "std")]
+impl Drop for Reporting {
+    fn drop(&mut self) {
+        REPORTING.with(|reporting| reporting.set(false));
     }
 }
 
+#[cfg(feature = "std")]
 /// A panic hook for use with
 /// [`std::panic::set_hook`](https://doc.rust-lang.org/nightly/std/panic/fn.set_hook.html)
 /// that logs panics into
 /// [`console.error`](https://developer.mozilla.org/en-US/docs/Web/API/Console/error).
 ///
-/// On non-wasm targets, prints the panic to `stderr`.
-pub fn hook(info: &panic::PanicInfo) {
-    hook_impl(info);
+/// On non-wasm targets and WASI, prints the panic to `stderr`.
+#[inline]
+#[cfg_attr(feature = "disabled", allow(unused_variables))]
+pub fn hook(info: &PanicHookInfo) {
+    #[cfg(all(feature = "minimal", not(feature = "disabled")))]
+    minimal::hook(info);
+    #[cfg(not(any(feature = "minimal", feature = "disabled")))]
+    let _ = hook_impl(&Arc::new(Config::default()), info);
+}
+
+#[cfg(feature = "std")]
+/// Format a panic the same way [`hook`](fn.hook.html) does, without logging
+/// it anywhere.
+///
+/// This is useful when writing your own panic hook that reports to several
+/// places but should still produce this crate's messages. On
+/// `wasm32-unknown-unknown` the message includes the JavaScript stack.
+pub fn format_panic(info: &PanicHookInfo) -> String {
+    Config::default().report(info).into_message()
+}
+
+#[cfg(feature = "std")]
+/// Start configuring a panic hook with a [`HookBuilder`].
+///
+/// ```
+/// console_error_panic_hook::builder()
+///     .with_prefix("myapp")
+///     .install();
+/// ```
+pub fn builder() -> HookBuilder {
+    HookBuilder::new()
+}
+
+#[cfg(feature = "std")]
+/// Install the `console.error` panic hook until the returned guard is dropped,
+/// at which point the previously installed hook is restored.
+///
+/// ```
+/// {
+///     let _guard = console_error_panic_hook::scoped();
+///     // Panics here are logged with `console.error`...
+/// }
+/// // ...and here they go to whichever hook was installed before.
+/// ```
+pub fn scoped() -> ScopedHook {
+    builder().install_scoped()
 }
 
+#[cfg(all(feature = "std", not(feature = "disabled")))]
+static SET_HOOK: Once = Once::new();
+
+#[cfg(feature = "std")]
 /// Set the `console.error` panic hook the first time this is called. Subsequent
 /// invocations do nothing.
 #[inline]
 pub fn set_once() {
-    use std::sync::Once;
-    static SET_HOOK: Once = Once::new();
+    #[cfg(not(feature = "disabled"))]
+    SET_HOOK.call_once(|| {
+        #[cfg(feature = "minimal")]
+        minimal::install();
+        #[cfg(not(feature = "minimal"))]
+        builder().install();
+    });
+}
+
+#[cfg(feature = "std")]
+/// Like [`set_once`](fn.set_once.html), but render panics with `formatter`
+/// instead of the default `PanicHookInfo` display.
+///
+/// ```
+/// use console_error_panic_hook::PanicHookInfo;
+///
+/// fn house_style(info: &PanicHookInfo) -> String {
+///     format!("[app] {}", info)
+/// }
+///
+/// console_error_panic_hook::set_once_with_formatter(house_style);
+/// ```
+#[inline]
+#[cfg_attr(feature = "disabled", allow(unused_variables))]
+pub fn set_once_with_formatter(formatter: Formatter) {
+    #[cfg(not(feature = "disabled"))]
     SET_HOOK.call_once(|| {
-        panic::set_hook(Box::new(hook));
+        builder().with_formatter(formatter).install();
     });
 }
+
+#[cfg(feature = "std")]
+/// Like [`set_once`](fn.set_once.html), but hand panics to `reporter` instead
+/// of logging them to the console.
+#[inline]
+#[cfg_attr(feature = "disabled", allow(unused_variables))]
+pub fn set_once_with_reporter(reporter: Box<dyn Reporter>) {
+    #[cfg(not(feature = "disabled"))]
+    SET_HOOK.call_once(|| {
+        builder().with_reporter(reporter).install();
+    });
+}
+
+#[cfg(feature = "std")]
+/// Like [`set_once`](fn.set_once.html), but hand each panic's formatted
+/// message to `f` instead of logging it to the console.
+///
+/// ```
+/// fn send_to_host(msg: &str) {
+///     // Forward `msg` over the host bridge...
+/// }
+///
+/// console_error_panic_hook::set_once_with(|msg| send_to_host(msg));
+/// ```
+#[inline]
+#[cfg_attr(feature = "disabled", allow(unused_variables))]
+pub fn set_once_with<F>(f: F)
+where
+    F: Fn(&str) + Send + Sync + 'static,
+{
+    #[cfg(not(feature = "disabled"))]
+    SET_HOOK.call_once(|| {
+        builder()
+            .with_reporter(Box::new(FnReporter::new(f)))
+            .install();
+    });
+}
+
+#[cfg(all(feature = "console-log", not(feature = "disabled")))]
+static SET_LOGGER: Once = Once::new();
+
+#[cfg(feature = "console-log")]
+/// Like [`set_once`](fn.set_once.html), but also route `log` records at
+/// `level` and above to the console with `console_log`. Subsequent
+/// invocations do nothing.
+///
+/// The panic hook goes first, so that a panic while setting up the logger is
+/// logged too. If another logger was set already, it is kept. Outside of
+/// JavaScript, this only installs the panic hook.
+///
+/// ```
+/// extern crate console_error_panic_hook;
+/// extern crate log;
+///
+/// fn main() {
+///     console_error_panic_hook::init_with_logging(log::LevelFilter::Info);
+///     log::info!("Logged with `console.info`");
+/// }
+/// ```
+#[inline]
+#[cfg_attr(feature = "disabled", allow(unused_variables))]
+pub fn init_with_logging(level: log::LevelFilter) {
+    #[cfg(not(feature = "disabled"))]
+    SET_LOGGER.call_once(|| {
+        set_once();
+        if let Some(level) = level.to_level() {
+            imp::init_logger(level);
+        }
+    });
+}
+
+#[cfg(feature = "alloc-error-hook")]
+/// Report failed memory allocations as panics, so that the panic hook reports
+/// them through its usual reporters. Without this, running out of memory
+/// aborts, which on wasm traps with nothing logged at all.
+///
+/// Building the usual report takes memory that may not be there, so before
+/// anything else, the hook logs "memory allocation of N bytes failed" from a
+/// static buffer. The panic itself just says "memory allocation failed".
+///
+/// This sets the hook with `std::alloc::set_alloc_error_hook`, which is only
+/// available on nightly toolchains. Elsewhere, this does nothing.
+///
+/// ```
+/// console_error_panic_hook::set_once();
+/// console_error_panic_hook::set_alloc_error_hook();
+/// ```
+#[inline]
+pub fn set_alloc_error_hook() {
+    #[cfg(all(has_alloc_error_hook, not(feature = "disabled")))]
+    std::alloc::set_alloc_error_hook(oom::alloc_error_hook);
+}
+
+#[cfg(feature = "std")]
+/// Uninstall the panic hook, restoring whichever hook was installed before it
+/// (often the default hook).
+///
+/// This does nothing if the hook was never installed, or was only installed
+/// through a [`ScopedHook`](struct.ScopedHook.html). Note that after this,
+/// [`set_once`](fn.set_once.html) and friends will not install the hook again;
+/// use [`HookBuilder::install`](struct.HookBuilder.html#method.install) to do
+/// that.
+pub fn unset() {
+    if let Some(previous) = lock(&PREVIOUS_HOOK).take() {
+        panic::set_hook(Box::new(move |info| previous(info)));
+    }
+}
+
+#[cfg(feature = "std")]
+/// Whether the panic hook is currently installed, either through
+/// [`set_once`](fn.set_once.html) and friends, or by a live
+/// [`ScopedHook`](struct.ScopedHook.html).
+///
+/// This only knows about installations made through this crate: if other
+/// code replaces the hook with `std::panic::set_hook`, this still returns
+/// `true`.
+pub fn is_set() -> bool {
+    lock(&PREVIOUS_HOOK).is_some() || scoped::ACTIVE_SCOPES.load(Ordering::SeqCst) > 0
+}
+
+#[cfg(feature = "std")]
+/// The report of the last panic that one of this crate's hooks reported, on
+/// any thread, if there has been one.
+///
+/// This lets code that calls into something that may panic, tests included,
+/// check what went wrong without parsing the console's output. Panics left
+/// unreported, for example while reporting is
+/// [disabled](fn.set_enabled.html) or with the `minimal` feature's `hook`,
+/// don't replace it.
+pub fn last_panic() -> Option<PanicReport> {
+    lock(&LAST_PANIC).clone()
+}
+
+#[cfg(feature = "std")]
+/// Like [`last_panic`](fn.last_panic.html), but clears it, so that the next
+/// call only returns a panic that happened since.
+pub fn take_last_panic() -> Option<PanicReport> {
+    lock(&LAST_PANIC).take()
+}
+
+#[cfg(feature = "std")]
+/// How many panics this crate's hooks have seen, on any thread, including
+/// those they didn't report, but not those of context providers.
+///
+/// A panic traps the module on wasm, so once this is more than zero, calling
+/// into it again is likely to fail: a render loop, for example, can check it
+/// to stop scheduling more calls. With the `panic-count` feature, JavaScript
+/// can call `panicCount()` for the same number.
+pub fn panic_count() -> usize {
+    PANIC_COUNT.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "std")]
+/// Turn panic reporting on or off at runtime. Reporting is enabled by default.
+///
+/// While disabled, installed hooks do nothing besides calling any chained
+/// previous hook: they don't report panics, and don't pause in the debugger,
+/// exit Node, abort, or throw either, whatever they were built with. So it is
+/// cheap to leave the hook installed and decide whether to report panics
+/// later on.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
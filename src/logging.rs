@@ -0,0 +1,110 @@
+use log::{logger, Level, Record};
+
+use super::{PanicReport, Reporter};
+
+/// A reporter that logs panics with the `log` crate, at `error` level, with
+/// the target `panic`, so that the application's logger filters, formats,
+/// and captures them like everything else. Requires the `log` feature.
+///
+/// Records carry the file and line that panicked, rather than those of this
+/// reporter. Add a [`ConsoleReporter`](struct.ConsoleReporter.html) too to
+/// keep logging panics directly, in case the logger isn't set up yet:
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, LogReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(LogReporter::new()))
+///     .install();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LogReporter;
+
+impl LogReporter {
+    /// Create a reporter that logs panics with the `log` crate.
+    pub fn new() -> LogReporter {
+        LogReporter
+    }
+}
+
+impl Reporter for LogReporter {
+    fn report(&self, report: &PanicReport) {
+        logger().log(
+            &Record::builder()
+                .level(Level::Error)
+                .target("panic")
+                .file(report.file())
+                .line(report.line())
+                .args(format_args!("{}", report.message()))
+                .build(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use log::{set_logger, set_max_level, LevelFilter, Log, Metadata};
+
+    use super::*;
+
+    /// The level, target, line, and message of each record.
+    type Logged = (Level, String, Option<u32>, String);
+
+    struct Recorder(Mutex<Vec<Logged>>);
+
+    impl Log for Recorder {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.0.lock().unwrap().push((
+                record.level(),
+                record.target().to_string(),
+                record.line(),
+                record.args().to_string(),
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static RECORDER: Recorder = Recorder(Mutex::new(Vec::new()));
+
+    #[test]
+    fn logs_panics_at_error_level() {
+        set_logger(&RECORDER).unwrap();
+        set_max_level(LevelFilter::Error);
+        LogReporter::new().report(&PanicReport {
+            message: "panicked at src/main.rs:3:5:\nboom".to_string(),
+            payload: Some("boom".to_string()),
+            file: Some("src/main.rs".to_string()),
+            line: Some(3),
+            column: Some(5),
+            thread: "main".to_string(),
+            timestamp: None,
+            uptime: None,
+            memory: None,
+            environment: None,
+            build: None,
+            breadcrumbs: Vec::new(),
+            context: Vec::new(),
+            state: Vec::new(),
+            module_name: None,
+            stack: None,
+            source_link: None,
+        });
+        assert_eq!(
+            *RECORDER.0.lock().unwrap(),
+            [(
+                Level::Error,
+                "panic".to_string(),
+                Some(3),
+                "panicked at src/main.rs:3:5:\nboom".to_string()
+            )]
+        );
+    }
+}
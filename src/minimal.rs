@@ -0,0 +1,60 @@
+//! The hook that `hook` and `set_once` use with the `minimal` feature. It
+//! pieces the message together from the payload and location, rather than
+//! formatting it, and leaves out everything else.
+
+use std::fmt::{self, Write};
+use std::panic;
+use std::str;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use super::fixed::Buffer;
+use super::{imp, lock, report, PanicHookInfo, ENABLED, PANIC_COUNT, PREVIOUS_HOOK};
+
+pub(crate) fn hook(info: &PanicHookInfo) {
+    PANIC_COUNT.fetch_add(1, Ordering::Relaxed);
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let mut msg = Buffer::new();
+    let _ = write_message(&mut msg, info);
+    imp::log_without_allocating(msg.as_str());
+}
+
+/// Install `hook`, saving the replaced hook for `unset`.
+pub(crate) fn install() {
+    let mut saved = lock(&PREVIOUS_HOOK);
+    let previous = Arc::new(panic::take_hook());
+    panic::set_hook(Box::new(hook));
+    *saved = Some(previous);
+}
+
+/// Write the same message as `PanicHookInfo`'s `Display` does, except for
+/// payloads that aren't strings, which are not described.
+fn write_message(msg: &mut Buffer, info: &PanicHookInfo) -> fmt::Result {
+    if let Some(location) = info.location() {
+        msg.write_str("panicked at ")?;
+        msg.write_str(location.file())?;
+        msg.write_str(":")?;
+        write_number(msg, location.line())?;
+        msg.write_str(":")?;
+        write_number(msg, location.column())?;
+        msg.write_str(":\n")?;
+    }
+    msg.write_str(report::payload_str(info).unwrap_or("Box<dyn Any>"))
+}
+
+/// Write `n` in decimal, without going through `fmt`.
+fn write_number(msg: &mut Buffer, mut n: u32) -> fmt::Result {
+    let mut digits = [0; 10];
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    msg.write_str(str::from_utf8(&digits[start..]).unwrap_or_default())
+}
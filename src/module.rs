@@ -0,0 +1,103 @@
+//! Just enough of a WebAssembly binary reader to find a module's sections.
+
+/// A cursor over the bytes of a WebAssembly module.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub(crate) fn byte(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.bytes.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    /// An unsigned LEB128 encoded integer.
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        let mut result = 0u32;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift >= 32 {
+                return None;
+            }
+            result |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(result);
+            }
+            shift += 7;
+        }
+    }
+
+    /// A length-prefixed UTF-8 string.
+    pub(crate) fn str(&mut self) -> Option<&'a str> {
+        let len = self.u32()? as usize;
+        ::std::str::from_utf8(self.bytes(len)?).ok()
+    }
+}
+
+/// A section of a WebAssembly module.
+pub(crate) struct Section<'a> {
+    pub(crate) id: u8,
+    /// For custom sections, the section's name.
+    pub(crate) name: Option<&'a str>,
+    /// The section's contents, after the name for custom sections.
+    pub(crate) payload: &'a [u8],
+    /// The offset of `payload` from the start of the module.
+    #[cfg_attr(not(feature = "dwarf"), allow(dead_code))]
+    pub(crate) offset: usize,
+}
+
+/// The id of custom sections.
+pub(crate) const CUSTOM: u8 = 0;
+
+/// The id of the code section.
+#[cfg(feature = "dwarf")]
+pub(crate) const CODE: u8 = 10;
+
+/// Every section in `module`, or `None` if it isn't a WebAssembly module.
+pub(crate) fn sections(module: &[u8]) -> Option<Vec<Section<'_>>> {
+    let mut reader = Reader::new(module);
+    if reader.bytes(4)? != b"\0asm" {
+        return None;
+    }
+    reader.bytes(4)?;
+
+    let mut sections = Vec::new();
+    while !reader.is_empty() {
+        let id = reader.byte()?;
+        let len = reader.u32()? as usize;
+        let offset = reader.pos;
+        let contents = reader.bytes(len)?;
+        let (name, payload, offset) = if id == CUSTOM {
+            let mut custom = Reader::new(contents);
+            let name = custom.str()?;
+            (Some(name), &contents[custom.pos..], offset + custom.pos)
+        } else {
+            (None, contents, offset)
+        };
+        sections.push(Section {
+            id,
+            name,
+            payload,
+            offset,
+        });
+    }
+    Some(sections)
+}
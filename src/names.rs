@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+use module::{self, Reader};
+
+/// The id of the function names subsection of the `name` custom section.
+const FUNCTION_NAMES: u8 = 1;
+
+/// A table of WebAssembly function names, used to rewrite
+/// `wasm-function[1234]` frames in captured stacks into readable names.
+///
+/// Release builds usually leave function names out of the stack traces
+/// engines produce. If you still have their names, either from the module's
+/// `name` section or from a table saved at build time, give them to
+/// [`HookBuilder::with_function_names`](struct.HookBuilder.html#method.with_function_names).
+///
+/// ```no_run
+/// use console_error_panic_hook::FunctionNames;
+///
+/// # let module_bytes: Vec<u8> = vec![];
+/// let names = FunctionNames::from_module(&module_bytes).unwrap_or_default();
+/// console_error_panic_hook::builder()
+///     .with_function_names(names)
+///     .install();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FunctionNames {
+    names: HashMap<u32, String>,
+}
+
+impl FunctionNames {
+    /// Create an empty table.
+    pub fn new() -> FunctionNames {
+        FunctionNames::default()
+    }
+
+    /// Read the function names from the `name` section of the given
+    /// WebAssembly module's bytes.
+    ///
+    /// Returns `None` if `module` isn't a WebAssembly module or has no `name`
+    /// section.
+    pub fn from_module(module: &[u8]) -> Option<FunctionNames> {
+        let sections = module::sections(module)?;
+        let section = sections
+            .iter()
+            .find(|section| section.id == module::CUSTOM && section.name == Some("name"))?;
+
+        let mut names = FunctionNames::new();
+        let mut reader = Reader::new(section.payload);
+        while !reader.is_empty() {
+            let id = reader.byte()?;
+            let len = reader.u32()? as usize;
+            let mut subsection = Reader::new(reader.bytes(len)?);
+            if id != FUNCTION_NAMES {
+                continue;
+            }
+            for _ in 0..subsection.u32()? {
+                let index = subsection.u32()?;
+                let name = subsection.str()?;
+                names.insert(index, name);
+            }
+        }
+        Some(names)
+    }
+
+    /// Name the function at `index`.
+    pub fn insert<S: Into<String>>(&mut self, index: u32, name: S) {
+        self.names.insert(index, name.into());
+    }
+
+    /// The name of the function at `index`, if it is known.
+    pub fn get(&self, index: u32) -> Option<&str> {
+        self.names.get(&index).map(|s| &s[..])
+    }
+}
+
+impl<S: Into<String>> FromIterator<(u32, S)> for FunctionNames {
+    fn from_iter<I: IntoIterator<Item = (u32, S)>>(iter: I) -> FunctionNames {
+        let mut names = FunctionNames::new();
+        for (index, name) in iter {
+            names.insert(index, name);
+        }
+        names
+    }
+}
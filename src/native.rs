@@ -0,0 +1,238 @@
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::env;
+#[cfg(all(stderr, feature = "color"))]
+use std::io::IsTerminal;
+#[cfg(stderr)]
+use std::io::{self, Write};
+use std::process;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(all(feature = "console-log", not(feature = "disabled")))]
+use log::Level;
+
+#[cfg(all(stderr, feature = "color"))]
+use super::console;
+#[cfg(target_os = "emscripten")]
+use super::emscripten;
+#[cfg(wasi_logging)]
+use super::wasi_logging;
+use super::{
+    suppress_reports, ConsoleReporter, Environment, NodeExit, PanicReport, Runtime, Settings,
+};
+
+pub(crate) fn now() -> Option<f64> {
+    let elapsed = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Some(elapsed.as_secs_f64() * 1000.0)
+}
+
+/// A native backtrace. Unless `force`d, this is only captured when enabled
+/// with the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables.
+pub(crate) fn capture_stack(force: bool) -> Option<String> {
+    let backtrace = if force {
+        Backtrace::force_capture()
+    } else {
+        Backtrace::capture()
+    };
+    match backtrace.status() {
+        BacktraceStatus::Captured => Some(backtrace.to_string()),
+        _ => None,
+    }
+}
+
+/// Log `msg`, returning whether that worked.
+#[cfg(wasi_logging)]
+pub(crate) fn console_error(msg: &str) -> bool {
+    wasi_logging::error(msg);
+    true
+}
+
+/// Log `msg`, returning whether that worked.
+#[cfg(target_os = "emscripten")]
+pub(crate) fn console_error(msg: &str) -> bool {
+    emscripten::console_error(msg);
+    true
+}
+
+/// Log `msg`, returning whether that worked.
+#[cfg(stderr)]
+pub(crate) fn console_error(msg: &str) -> bool {
+    writeln!(io::stderr(), "{}", msg).is_ok()
+}
+
+/// Log `msg`. Writing to `stderr` doesn't allocate, though logging through
+/// Emscripten's console or `wasi:logging` may.
+pub(crate) fn log_without_allocating(msg: &str) -> bool {
+    console_error(msg)
+}
+
+pub(crate) fn detect_runtime() -> Runtime {
+    Runtime::Native
+}
+
+/// There are no JavaScript globals outside of JavaScript.
+pub(crate) fn global_settings(_settings: &mut Settings) {}
+
+/// The `CONSOLE_ERROR_PANIC_HOOK` environment variable, which takes the same
+/// list of settings as the `panic_hook` query parameter does on the web.
+pub(crate) fn settings_list() -> Option<String> {
+    env::var("CONSOLE_ERROR_PANIC_HOOK").ok()
+}
+
+/// Whether the `RUST_BACKTRACE` environment variable is set to anything but
+/// `0`, if it is set.
+pub(crate) fn backtrace_switch() -> Option<bool> {
+    env::var_os("RUST_BACKTRACE").map(|switch| switch != "0")
+}
+
+/// There are no promises to reject outside of JavaScript.
+pub(crate) fn on_unhandled_rejection<F: Fn(&str, Option<String>) + 'static>(_handler: F) {}
+
+/// Nothing traps outside of wasm.
+pub(crate) fn on_trap<F: Fn(&str) -> bool + 'static>(_handler: F) {}
+
+/// Call `f` on a thread of its own, so that a panic in it unwinds that thread
+/// rather than aborting, even while reporting a panic. That panic is left
+/// alone by our hooks. Without threads, `f` is called directly.
+pub(crate) fn call_isolated<T, F>(f: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    // A failed spawn takes `f` with it, so don't try on WASI and Emscripten,
+    // which have no threads to spawn.
+    if cfg!(target_family = "wasm") {
+        return Some(f());
+    }
+    thread::Builder::new()
+        .name("panic reporting".to_string())
+        .spawn(|| {
+            suppress_reports();
+            f()
+        })
+        .ok()?
+        .join()
+        .ok()
+}
+
+/// There are no JavaScript callbacks outside of JavaScript.
+pub(crate) fn has_js_callbacks() -> bool {
+    false
+}
+
+/// There are no events to dispatch outside of JavaScript.
+pub(crate) fn dispatch_panic_event(_report: &PanicReport) {}
+
+/// There are no JavaScript callbacks to call outside of JavaScript.
+pub(crate) fn call_js_callbacks(_report: &PanicReport) {}
+
+/// There is no parent page outside of an iframe.
+pub(crate) fn post_to_parent(_report: &PanicReport, _target_origin: &str) {}
+
+/// There are no `BroadcastChannel`s outside of JavaScript.
+pub(crate) fn broadcast(_report: &PanicReport, _channel: &str) {}
+
+/// The operating system and the available parallelism. There is no user
+/// agent outside of JavaScript.
+pub(crate) fn environment() -> Option<Environment> {
+    Some(Environment {
+        user_agent: None,
+        platform: Some(env::consts::OS.to_string()),
+        hardware_concurrency: thread::available_parallelism().ok().map(|n| n.get() as u32),
+    })
+}
+
+/// There is no `performance.now()` outside of JavaScript.
+pub(crate) fn uptime() -> Option<f64> {
+    None
+}
+
+/// There is no Performance timeline outside of JavaScript.
+pub(crate) fn performance_mark(_report: &PanicReport, _name: &str) {}
+
+/// There is no `sessionStorage` outside of JavaScript.
+pub(crate) fn store_in_session(_report: &PanicReport, _key: &str) {}
+
+/// There is no IndexedDB outside of JavaScript.
+#[cfg(feature = "indexeddb")]
+pub(crate) fn append_to_indexeddb(_report: &PanicReport, _capacity: usize) {}
+
+/// There is no IndexedDB outside of JavaScript, so no panics are stored.
+#[cfg(feature = "indexeddb")]
+pub(crate) fn stored_in_indexeddb(f: Box<dyn FnOnce(Vec<PanicReport>)>) {
+    f(Vec::new());
+}
+
+/// There is no IndexedDB outside of JavaScript.
+#[cfg(feature = "indexeddb")]
+pub(crate) fn clear_indexeddb() {}
+
+/// There are no JavaScript objects to serialize outside of JavaScript.
+pub(crate) fn report_to_json(_report: &PanicReport, _with_stack: bool) -> Option<String> {
+    None
+}
+
+/// There are no JavaScript objects to serialize outside of JavaScript.
+pub(crate) fn to_json(_reports: &[PanicReport], _with_stack: bool) -> Option<String> {
+    None
+}
+
+/// There is no `fetch` outside of JavaScript.
+pub(crate) fn post(
+    _url: &str,
+    _content_type: &str,
+    _headers: &[(String, String)],
+    _body: &str,
+) -> bool {
+    false
+}
+
+/// There is no `sendBeacon` outside of JavaScript.
+pub(crate) fn send_beacon(_url: &str, _payload: &str) -> bool {
+    false
+}
+
+/// There are no WebSockets outside of JavaScript.
+pub(crate) fn send_websocket(_url: &str, _payload: &str) -> bool {
+    false
+}
+
+/// There are no WebSockets outside of JavaScript.
+pub(crate) fn open_websocket(_url: &str) {}
+
+/// There is no console to log to outside of JavaScript.
+#[cfg(all(feature = "console-log", not(feature = "disabled")))]
+pub(crate) fn init_logger(_level: Level) {}
+
+/// There is no JavaScript debugger to pause in outside of JavaScript.
+pub(crate) fn debugger() {}
+
+/// There is no Node process to exit.
+pub(crate) fn node_exit(_node_exit: NodeExit) {}
+
+pub(crate) fn abort() {
+    process::abort();
+}
+
+/// There is no JavaScript to throw to outside of JavaScript.
+pub(crate) fn throw(_msg: &str) {}
+
+/// There are no objects to log to `stderr`, so this writes the message alone.
+pub(crate) fn console_record(report: &PanicReport) -> bool {
+    console_error(report.message())
+}
+
+/// There is no main thread to post to outside of a Web Worker.
+pub(crate) fn post_to_main_thread(_report: &PanicReport) {}
+
+/// There are no objects to log to `stderr`, so this writes the message alone.
+pub(crate) fn console_report(_console: &ConsoleReporter, report: &PanicReport) -> bool {
+    #[cfg(all(stderr, feature = "color"))]
+    {
+        if io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none() {
+            return console_error(&console::colorize(report));
+        }
+    }
+
+    console_error(report.message())
+}
@@ -0,0 +1,48 @@
+//! Reporting failed allocations, when there may be no memory left to report
+//! them with.
+
+use std::alloc::Layout;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use super::fixed::Buffer;
+use super::{imp, lock, Config, REPORTING};
+
+/// The size of the allocation that failed, until its panic is reported. No
+/// allocation of zero bytes ever fails, so zero means there is none.
+static FAILED_ALLOCATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Where failed allocations are formatted. Being static, it is there before
+/// any allocation can fail.
+static BUFFER: Mutex<Buffer> = Mutex::new(Buffer::new());
+
+/// The hook [`set_alloc_error_hook`](../fn.set_alloc_error_hook.html) sets.
+pub(crate) fn alloc_error_hook(layout: Layout) {
+    // If reporting a panic is what failed to allocate, panicking again would
+    // only abort. Returning aborts just the same, and keeps whatever was
+    // logged before.
+    if REPORTING.with(|reporting| reporting.get()) {
+        return;
+    }
+    FAILED_ALLOCATION.store(layout.size(), Ordering::Relaxed);
+    // A formatted payload would be allocated before the panic hook even runs,
+    // so the size is passed along on the side.
+    panic!("memory allocation failed");
+}
+
+/// If the panic being reported is a failed allocation, log how big it was,
+/// before anything else tries to allocate.
+pub(crate) fn report_failed_allocation(config: &Config) {
+    let size = FAILED_ALLOCATION.swap(0, Ordering::Relaxed);
+    if size == 0 {
+        return;
+    }
+    let mut buffer = lock(&BUFFER);
+    *buffer = Buffer::new();
+    if let Some(ref prefix) = config.prefix {
+        let _ = write!(buffer, "{} ", prefix);
+    }
+    let _ = write!(buffer, "memory allocation of {} bytes failed", size);
+    imp::log_without_allocating(buffer.as_str());
+}
@@ -0,0 +1,209 @@
+use std::time::{Duration, UNIX_EPOCH};
+
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, Severity};
+
+use super::{PanicReport, Reporter};
+
+/// A reporter that emits panics as OpenTelemetry log records, through a
+/// [`Logger`](https://docs.rs/opentelemetry/latest/opentelemetry/logs/trait.Logger.html)
+/// from the application's own logger provider and exporter. Requires the
+/// `otel` feature.
+///
+/// Each panic becomes an `ERROR` record with the panic's message as its body,
+/// and attributes following OpenTelemetry's semantic conventions:
+/// `code.file.path`, `code.line.number`, `code.column.number`,
+/// `thread.name`, `exception.type` (always `panic`), `exception.message`
+/// with the payload, and `exception.stacktrace`.
+///
+/// ```
+/// # extern crate console_error_panic_hook;
+/// # extern crate opentelemetry;
+/// use console_error_panic_hook::OtelReporter;
+/// use opentelemetry::logs::LoggerProvider;
+/// # let logger_provider = opentelemetry::logs::NoopLoggerProvider::new();
+///
+/// let logger = logger_provider.logger("my_app");
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(OtelReporter::new(logger)))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct OtelReporter<L> {
+    logger: L,
+}
+
+impl<L> OtelReporter<L>
+where
+    L: Logger + Send + Sync,
+{
+    /// Create a reporter that emits panics with `logger`.
+    pub fn new(logger: L) -> OtelReporter<L> {
+        OtelReporter { logger }
+    }
+}
+
+impl<L> Reporter for OtelReporter<L>
+where
+    L: Logger + Send + Sync,
+{
+    fn report(&self, report: &PanicReport) {
+        let mut record = self.logger.create_log_record();
+        record.set_severity_number(Severity::Error);
+        record.set_severity_text("ERROR");
+        record.set_body(AnyValue::from(report.message().to_string()));
+        // `SystemTime::now` panics on `wasm32-unknown-unknown`, so use the
+        // time the report already has.
+        if let Some(timestamp) = report.timestamp() {
+            record.set_timestamp(UNIX_EPOCH + Duration::from_secs_f64(timestamp / 1000.0));
+        }
+
+        if let Some(file) = report.file() {
+            record.add_attribute("code.file.path", file.to_string());
+        }
+        if let Some(line) = report.line() {
+            record.add_attribute("code.line.number", i64::from(line));
+        }
+        if let Some(column) = report.column() {
+            record.add_attribute("code.column.number", i64::from(column));
+        }
+        record.add_attribute("thread.name", report.thread().to_string());
+        record.add_attribute("exception.type", "panic");
+        if let Some(payload) = report.payload() {
+            record.add_attribute("exception.message", payload.to_string());
+        }
+        if let Some(stack) = report.stack() {
+            record.add_attribute("exception.stacktrace", stack.to_string());
+        }
+
+        self.logger.emit(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::sync::Mutex;
+    use std::time::SystemTime;
+
+    use opentelemetry::logs::{Logger, Severity};
+    use opentelemetry::Key;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Record {
+        severity: Option<Severity>,
+        body: Option<AnyValue>,
+        timestamp: Option<SystemTime>,
+        attributes: Vec<(Key, AnyValue)>,
+    }
+
+    impl LogRecord for Record {
+        fn set_event_name(&mut self, _name: &'static str) {}
+        fn set_target<T>(&mut self, _target: T)
+        where
+            T: Into<Cow<'static, str>>,
+        {
+        }
+        fn set_timestamp(&mut self, timestamp: SystemTime) {
+            self.timestamp = Some(timestamp);
+        }
+        fn set_observed_timestamp(&mut self, _timestamp: SystemTime) {}
+        fn set_severity_text(&mut self, _text: &'static str) {}
+        fn set_severity_number(&mut self, number: Severity) {
+            self.severity = Some(number);
+        }
+        fn set_body(&mut self, body: AnyValue) {
+            self.body = Some(body);
+        }
+        fn add_attributes<I, K, V>(&mut self, attributes: I)
+        where
+            I: IntoIterator<Item = (K, V)>,
+            K: Into<Key>,
+            V: Into<AnyValue>,
+        {
+            for (key, value) in attributes {
+                self.add_attribute(key, value);
+            }
+        }
+        fn add_attribute<K, V>(&mut self, key: K, value: V)
+        where
+            K: Into<Key>,
+            V: Into<AnyValue>,
+        {
+            self.attributes.push((key.into(), value.into()));
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<Record>>);
+
+    impl Logger for Recorder {
+        type LogRecord = Record;
+
+        fn create_log_record(&self) -> Record {
+            Record::default()
+        }
+        fn emit(&self, record: Record) {
+            self.0.lock().unwrap().push(record);
+        }
+        fn event_enabled(&self, _level: Severity, _target: &str, _name: Option<&str>) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn emits_error_records() {
+        let reporter = OtelReporter::new(Recorder::default());
+        reporter.report(&PanicReport {
+            message: "panicked at src/main.rs:3:5:\nboom".to_string(),
+            payload: Some("boom".to_string()),
+            file: Some("src/main.rs".to_string()),
+            line: Some(3),
+            column: Some(5),
+            thread: "main".to_string(),
+            timestamp: Some(1500.0),
+            uptime: None,
+            memory: None,
+            environment: None,
+            build: None,
+            breadcrumbs: Vec::new(),
+            context: Vec::new(),
+            state: Vec::new(),
+            module_name: None,
+            stack: None,
+            source_link: None,
+        });
+
+        let records = reporter.logger.0.lock().unwrap();
+        let record = &records[0];
+        assert_eq!(record.severity, Some(Severity::Error));
+        assert_eq!(
+            record.body,
+            Some(AnyValue::from(
+                "panicked at src/main.rs:3:5:\nboom".to_string()
+            ))
+        );
+        assert_eq!(
+            record.timestamp,
+            Some(UNIX_EPOCH + Duration::from_millis(1500))
+        );
+        let attribute = |key: &'static str| {
+            record
+                .attributes
+                .iter()
+                .find(|(k, _)| *k == Key::from_static_str(key))
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(
+            attribute("code.file.path"),
+            Some(AnyValue::from("src/main.rs".to_string()))
+        );
+        assert_eq!(attribute("code.line.number"), Some(AnyValue::from(3i64)));
+        assert_eq!(
+            attribute("exception.message"),
+            Some(AnyValue::from("boom".to_string()))
+        );
+        assert_eq!(attribute("exception.stacktrace"), None);
+    }
+}
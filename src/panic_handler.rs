@@ -0,0 +1,47 @@
+//! A `#[panic_handler]` for `no_std` wasm modules, which have no panic hook to
+//! install.
+
+#[cfg(target_arch = "wasm32")]
+use core::arch::wasm32 as arch;
+#[cfg(target_arch = "wasm64")]
+use core::arch::wasm64 as arch;
+use core::fmt::Write;
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use fixed::Buffer;
+
+cfg_if! {
+    if #[cfg(feature = "wasm-bindgen")] {
+        use wasm_bindgen::prelude::*;
+
+        #[wasm_bindgen]
+        extern "C" {
+            #[wasm_bindgen(js_namespace = console)]
+            fn error(msg: &str);
+        }
+    } else {
+        use raw::console_error as error;
+    }
+}
+
+/// Whether a panic is being reported. If formatting it panics in turn, we
+/// end up back here, and must not format anything again.
+static REPORTING: AtomicBool = AtomicBool::new(false);
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    if REPORTING.swap(true, Ordering::Relaxed) {
+        error("panic while reporting panic\n\n");
+        arch::unreachable()
+    }
+
+    // There may be no allocator, so the message is formatted on the stack,
+    // and anything that doesn't fit is cut off.
+    let mut msg = Buffer::new();
+    // See `with_whitespace` in `wasm.rs` for why the trailing newlines.
+    let _ = write!(msg, "{}\n\n", info);
+    error(msg.as_str());
+
+    arch::unreachable()
+}
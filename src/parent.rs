@@ -0,0 +1,52 @@
+use super::{imp, PanicReport, Reporter};
+
+/// A reporter that, inside an iframe, posts panics to the embedding page with
+/// `window.parent.postMessage`, for when nobody is watching the iframe's own
+/// console.
+///
+/// Panics are only delivered to a parent whose origin matches
+/// `target_origin`, as with `postMessage`. Use `"*"` to post to any origin,
+/// such as from a sandboxed iframe, whose origin is opaque, but keep in mind
+/// that any page could then embed the iframe and read its panics.
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, ParentReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(ParentReporter::new("https://example.com")))
+///     .install();
+/// ```
+///
+/// Each panic is posted just like a
+/// [`WorkerReporter`](struct.WorkerReporter.html) posts it. Outside of an
+/// iframe, and on non-wasm targets, this does nothing. Listen for them in the
+/// embedding page with:
+///
+/// ```js
+/// addEventListener("message", ({ source, data }) => {
+///   if (source === iframe.contentWindow && data && data.type === "console_error_panic_hook") {
+///     console.error(`The iframe panicked:\n\n${data.report.message}`);
+///   }
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParentReporter {
+    target_origin: String,
+}
+
+impl ParentReporter {
+    /// Create a reporter that posts panics to a parent page with the origin
+    /// `target_origin`.
+    pub fn new<S: Into<String>>(target_origin: S) -> ParentReporter {
+        ParentReporter {
+            target_origin: target_origin.into(),
+        }
+    }
+}
+
+impl Reporter for ParentReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::post_to_parent(report, &self.target_origin);
+    }
+}
@@ -0,0 +1,59 @@
+use super::{imp, PanicReport, Reporter};
+
+/// A reporter that records each panic on the Performance timeline with
+/// `performance.mark("rust-panic", { detail })`, so that it shows up in
+/// profiles and exported traces next to the work that led to it.
+///
+/// The mark's `detail` holds the same fields as the detail of
+/// [`EventReporter`](struct.EventReporter.html)'s events, and it can be read
+/// back like any other mark:
+///
+/// ```js
+/// for (const mark of performance.getEntriesByName("rust-panic")) {
+///   console.log(mark.startTime, mark.detail.message);
+/// }
+/// ```
+///
+/// Where there is no `performance`, and on non-wasm targets, this does
+/// nothing, so add a [`ConsoleReporter`](struct.ConsoleReporter.html) too to
+/// keep logging panics:
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, PerformanceReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(PerformanceReporter::new()))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct PerformanceReporter {
+    name: String,
+}
+
+impl PerformanceReporter {
+    /// Create a reporter that marks panics as `rust-panic`.
+    pub fn new() -> PerformanceReporter {
+        PerformanceReporter {
+            name: "rust-panic".to_string(),
+        }
+    }
+
+    /// Name the marks `name` instead of `rust-panic`.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> PerformanceReporter {
+        self.name = name.into();
+        self
+    }
+}
+
+impl Default for PerformanceReporter {
+    fn default() -> PerformanceReporter {
+        PerformanceReporter::new()
+    }
+}
+
+impl Reporter for PerformanceReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::performance_mark(report, &self.name);
+    }
+}
@@ -0,0 +1,196 @@
+//! Logging through a plain wasm import, for modules built without
+//! `wasm-bindgen`.
+//!
+//! The embedder provides one function, `error`, in the
+//! `console_error_panic_hook` import module. It takes a pointer to and the
+//! length of the UTF-8 message in the module's memory.
+
+#[cfg(feature = "std")]
+use super::{ConsoleReporter, Environment, NodeExit, PanicReport, Runtime, Settings};
+
+#[link(wasm_import_module = "console_error_panic_hook")]
+extern "C" {
+    #[link_name = "error"]
+    fn error_raw(ptr: *const u8, len: usize);
+}
+
+/// Log `msg`. The import has no way to report failure, so this always
+/// returns `true`.
+pub(crate) fn console_error(msg: &str) -> bool {
+    unsafe {
+        error_raw(msg.as_ptr(), msg.len());
+    }
+    true
+}
+
+/// Log `msg`. The import reads it straight out of memory, so nothing is
+/// allocated.
+#[cfg(feature = "std")]
+pub(crate) fn log_without_allocating(msg: &str) -> bool {
+    console_error(msg)
+}
+
+/// There is no clock to read without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn now() -> Option<f64> {
+    None
+}
+
+/// There is no `Error` to capture a stack from, but devtools still show the
+/// stack of the `console.error` call made by the embedder.
+#[cfg(feature = "std")]
+pub(crate) fn capture_stack(_force: bool) -> Option<String> {
+    None
+}
+
+/// Without JavaScript bindings, there is no telling.
+#[cfg(feature = "std")]
+pub(crate) fn detect_runtime() -> Runtime {
+    Runtime::Unknown
+}
+
+/// There are no events to dispatch without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn dispatch_panic_event(_report: &PanicReport) {}
+
+/// There are no JavaScript globals without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn global_settings(_settings: &mut Settings) {}
+
+/// There is no URL to read settings from without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn settings_list() -> Option<String> {
+    None
+}
+
+/// There is no `RUST_BACKTRACE` global without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn backtrace_switch() -> Option<bool> {
+    None
+}
+
+/// There are no promises to reject without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn on_unhandled_rejection<F: Fn(&str, Option<String>) + 'static>(_handler: F) {}
+
+/// There are no uncaught JavaScript errors to listen for without JavaScript
+/// bindings.
+#[cfg(feature = "std")]
+pub(crate) fn on_trap<F: Fn(&str) -> bool + 'static>(_handler: F) {}
+
+/// There is nothing to call `f` through without JavaScript bindings, so a
+/// panic in it can't be contained.
+#[cfg(feature = "std")]
+pub(crate) fn call_isolated<T, F: FnOnce() -> T>(f: F) -> Option<T> {
+    Some(f())
+}
+
+/// There are no JavaScript callbacks without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn has_js_callbacks() -> bool {
+    false
+}
+
+/// There are no JavaScript callbacks to call without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn call_js_callbacks(_report: &PanicReport) {}
+
+/// There are no iframes without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn post_to_parent(_report: &PanicReport, _target_origin: &str) {}
+
+/// There are no `BroadcastChannel`s without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn broadcast(_report: &PanicReport, _channel: &str) {}
+
+/// There is no `navigator` to ask without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn environment() -> Option<Environment> {
+    None
+}
+
+/// There is no `performance.now()` without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn uptime() -> Option<f64> {
+    None
+}
+
+/// There is no Performance timeline without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn performance_mark(_report: &PanicReport, _name: &str) {}
+
+/// There is no `sessionStorage` without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn store_in_session(_report: &PanicReport, _key: &str) {}
+
+/// There are no JavaScript objects to serialize without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn report_to_json(_report: &PanicReport, _with_stack: bool) -> Option<String> {
+    None
+}
+
+/// There are no JavaScript objects to serialize without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn to_json(_reports: &[PanicReport], _with_stack: bool) -> Option<String> {
+    None
+}
+
+/// There is no `fetch` without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn post(
+    _url: &str,
+    _content_type: &str,
+    _headers: &[(String, String)],
+    _body: &str,
+) -> bool {
+    false
+}
+
+/// There is no `sendBeacon` without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn send_beacon(_url: &str, _payload: &str) -> bool {
+    false
+}
+
+/// There are no WebSockets without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn send_websocket(_url: &str, _payload: &str) -> bool {
+    false
+}
+
+/// There are no WebSockets without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn open_websocket(_url: &str) {}
+
+/// There is no JavaScript debugger to pause in without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn debugger() {}
+
+/// There is no Node process to exit.
+#[cfg(feature = "std")]
+pub(crate) fn node_exit(_node_exit: NodeExit) {}
+
+/// The panic traps the module anyway, so there is nothing to do here.
+#[cfg(feature = "std")]
+pub(crate) fn abort() {}
+
+/// There is nothing to throw JavaScript exceptions with without JavaScript
+/// bindings.
+#[cfg(feature = "std")]
+pub(crate) fn throw(_msg: &str) {}
+
+/// Only the message crosses the import, so this logs it alone.
+#[cfg(feature = "std")]
+pub(crate) fn console_record(report: &PanicReport) -> bool {
+    console_error(report.message())
+}
+
+/// There are no Web Workers without JavaScript bindings.
+#[cfg(feature = "std")]
+pub(crate) fn post_to_main_thread(_report: &PanicReport) {}
+
+/// Only the message crosses the import, so this logs it alone.
+#[cfg(feature = "std")]
+pub(crate) fn console_report(_console: &ConsoleReporter, report: &PanicReport) -> bool {
+    console_error(report.message())
+}
@@ -0,0 +1,117 @@
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use super::PanicReport;
+
+/// What redacted text is replaced with.
+const REDACTED: &str = "[redacted]";
+
+/// Text to keep out of panic reports.
+#[derive(Debug, Default)]
+pub(crate) struct Redactions {
+    literals: Vec<String>,
+    #[cfg(feature = "regex")]
+    patterns: Vec<Regex>,
+}
+
+impl Redactions {
+    pub(crate) fn add_literal(&mut self, literal: String) {
+        // Replacing the empty string would put `[redacted]` between every
+        // character.
+        if !literal.is_empty() {
+            self.literals.push(literal);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    pub(crate) fn add_pattern(&mut self, pattern: Regex) {
+        self.patterns.push(pattern);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        #[cfg(feature = "regex")]
+        {
+            if !self.patterns.is_empty() {
+                return false;
+            }
+        }
+        self.literals.is_empty()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        #[cfg(feature = "regex")]
+        {
+            self.literals.len() + self.patterns.len()
+        }
+        #[cfg(not(feature = "regex"))]
+        {
+            self.literals.len()
+        }
+    }
+
+    /// Replace everything in `text` that is to be redacted.
+    ///
+    /// All matches are found in the original text before any of them is
+    /// replaced, so that one redaction never matches part of the marker
+    /// another one left behind.
+    pub(crate) fn apply(&self, text: &mut String) {
+        let mut matches: Vec<(usize, usize)> = Vec::new();
+        for literal in &self.literals {
+            matches.extend(
+                text.match_indices(literal.as_str())
+                    .map(|(start, found)| (start, start + found.len())),
+            );
+        }
+        #[cfg(feature = "regex")]
+        {
+            for pattern in &self.patterns {
+                matches.extend(
+                    pattern
+                        .find_iter(text)
+                        .filter(|found| !found.is_empty())
+                        .map(|found| (found.start(), found.end())),
+                );
+            }
+        }
+        if matches.is_empty() {
+            return;
+        }
+        matches.sort_unstable();
+
+        let mut redacted = String::with_capacity(text.len());
+        let mut copied = 0;
+        for (start, end) in matches {
+            if end <= copied {
+                continue;
+            }
+            // Matches that overlap one another are redacted together.
+            if start >= copied {
+                redacted.push_str(&text[copied..start]);
+                redacted.push_str(REDACTED);
+            }
+            copied = end;
+        }
+        redacted.push_str(&text[copied..]);
+        *text = redacted;
+    }
+
+    /// Redact the application data in `report`: its context, the keys and
+    /// values of its state, and the categories and messages of its
+    /// breadcrumbs.
+    pub(crate) fn apply_to_sections(&self, report: &mut PanicReport) {
+        if self.is_empty() {
+            return;
+        }
+        for context in &mut report.context {
+            self.apply(context);
+        }
+        for &mut (ref mut key, ref mut value) in &mut report.state {
+            self.apply(key);
+            self.apply(value);
+        }
+        for breadcrumb in &mut report.breadcrumbs {
+            self.apply(&mut breadcrumb.category);
+            self.apply(&mut breadcrumb.message);
+        }
+    }
+}
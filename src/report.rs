@@ -0,0 +1,613 @@
+use std::any::Any;
+use std::fmt;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use super::{imp, lock, Breadcrumb, PanicHookInfo};
+
+/// A panic that is being reported.
+///
+/// Besides the formatted message, this exposes the individual pieces of the
+/// panic for reporters that want to send them somewhere in a machine-readable
+/// form.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct PanicReport {
+    pub(crate) message: String,
+    pub(crate) payload: Option<String>,
+    pub(crate) file: Option<String>,
+    pub(crate) line: Option<u32>,
+    pub(crate) column: Option<u32>,
+    pub(crate) thread: String,
+    pub(crate) timestamp: Option<f64>,
+    pub(crate) uptime: Option<f64>,
+    pub(crate) memory: Option<u64>,
+    pub(crate) environment: Option<Environment>,
+    pub(crate) build: Option<BuildInfo>,
+    pub(crate) breadcrumbs: Vec<Breadcrumb>,
+    pub(crate) context: Vec<String>,
+    pub(crate) state: Vec<(String, String)>,
+    pub(crate) module_name: Option<String>,
+    pub(crate) stack: Option<String>,
+    pub(crate) source_link: Option<String>,
+}
+
+impl PanicReport {
+    /// A report of the given panic, with an empty message.
+    pub(crate) fn new(info: &PanicHookInfo) -> PanicReport {
+        let location = info.location();
+        PanicReport {
+            message: String::new(),
+            payload: payload_str(info).map(|s| s.to_string()),
+            file: location.map(|l| l.file().to_string()),
+            line: location.map(|l| l.line()),
+            column: location.map(|l| l.column()),
+            thread: current_thread(),
+            timestamp: imp::now(),
+            uptime: None,
+            memory: None,
+            environment: None,
+            build: None,
+            breadcrumbs: Vec::new(),
+            context: Vec::new(),
+            state: Vec::new(),
+            module_name: None,
+            stack: None,
+            source_link: None,
+        }
+    }
+
+    /// A report of a panic with only its `payload` to go on, such as one
+    /// caught without a hook from this crate to report it.
+    pub(crate) fn from_payload(payload: &(dyn Any + Send)) -> PanicReport {
+        let payload = downcast_str(payload).map(|s| s.to_string());
+        let message = payload
+            .clone()
+            .unwrap_or_else(|| "Box<dyn Any>".to_string());
+        PanicReport::without_location(message, payload)
+    }
+
+    /// A report of something with no location to go on, such as an error
+    /// from JavaScript.
+    pub(crate) fn without_location(message: String, payload: Option<String>) -> PanicReport {
+        PanicReport {
+            message,
+            payload,
+            file: None,
+            line: None,
+            column: None,
+            thread: current_thread(),
+            timestamp: imp::now(),
+            uptime: None,
+            memory: None,
+            environment: None,
+            build: None,
+            breadcrumbs: Vec::new(),
+            context: Vec::new(),
+            state: Vec::new(),
+            module_name: None,
+            stack: None,
+            source_link: None,
+        }
+    }
+
+    pub(crate) fn into_message(self) -> String {
+        self.message
+    }
+
+    /// The formatted panic message, exactly as it would be logged to the
+    /// console.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The panic's payload, if it was a string. This is what was passed to
+    /// `panic!`, without the location.
+    pub fn payload(&self) -> Option<&str> {
+        self.payload.as_ref().map(|s| &s[..])
+    }
+
+    /// The source file the panic originated from, if known.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_ref().map(|s| &s[..])
+    }
+
+    /// The line the panic originated from, if known.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// The column the panic originated from, if known.
+    pub fn column(&self) -> Option<u32> {
+        self.column
+    }
+
+    /// The name of the thread that panicked or, if it has none, its
+    /// `ThreadId`. With threaded wasm, this tells apart the workers sharing
+    /// the hook.
+    pub fn thread(&self) -> &str {
+        &self.thread
+    }
+
+    /// When the panic happened, in milliseconds since the Unix epoch, if the
+    /// clock is available.
+    pub fn timestamp(&self) -> Option<f64> {
+        self.timestamp
+    }
+
+    /// When the panic happened, in milliseconds since the page or process
+    /// started, as `performance.now()` tells, if the hook was configured
+    /// with [timestamps](struct.HookBuilder.html#method.with_timestamp) and
+    /// there is a `performance` to ask.
+    pub fn uptime(&self) -> Option<f64> {
+        self.uptime
+    }
+
+    /// The size of the module's linear memory when the panic was reported, in
+    /// bytes, if the hook was configured to
+    /// [include it](struct.HookBuilder.html#method.with_memory) and this is a
+    /// `wasm32` target.
+    pub fn memory(&self) -> Option<u64> {
+        self.memory
+    }
+
+    /// What the panic happened on, if the hook was configured to
+    /// [capture it](struct.HookBuilder.html#method.with_environment).
+    pub fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    /// Which build panicked, if the hook was
+    /// [given one](struct.HookBuilder.html#method.with_build_info).
+    pub fn build(&self) -> Option<&BuildInfo> {
+        self.build.as_ref()
+    }
+
+    /// The breadcrumbs recorded with
+    /// [`add_breadcrumb`](fn.add_breadcrumb.html) before the panic, oldest
+    /// first.
+    pub fn breadcrumbs(&self) -> &[Breadcrumb] {
+        &self.breadcrumbs
+    }
+
+    /// What the panicking thread was doing, from the
+    /// [`context`](fn.context.html) guards alive when it panicked, outermost
+    /// first.
+    pub fn context(&self) -> &[String] {
+        &self.context
+    }
+
+    /// The application's state when it panicked, as key-value pairs from the
+    /// providers added with
+    /// [`add_context_provider`](fn.add_context_provider.html).
+    pub fn state(&self) -> &[(String, String)] {
+        &self.state
+    }
+
+    /// The name of the wasm module that panicked, if the hook was
+    /// [given one](struct.HookBuilder.html#method.with_module_name).
+    pub fn module_name(&self) -> Option<&str> {
+        self.module_name.as_ref().map(|s| &s[..])
+    }
+
+    /// The stack captured when the panic was reported, if the hook's verbosity
+    /// asked for one and the target can capture them. On
+    /// `wasm32-unknown-unknown` this is the JavaScript `new Error().stack`.
+    ///
+    /// The stack is also included in the [`message`](#method.message).
+    pub fn stack(&self) -> Option<&str> {
+        self.stack.as_ref().map(|s| &s[..])
+    }
+
+    /// A link to the panic's location in the source, if the hook was
+    /// configured with a
+    /// [source link template](struct.HookBuilder.html#method.with_source_link).
+    pub fn source_link(&self) -> Option<&str> {
+        self.source_link.as_ref().map(|s| &s[..])
+    }
+
+    /// Serialize this report as a single line of JSON.
+    ///
+    /// Requires the `json` feature.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        // Every field is a plain string or number, so this can't fail.
+        serde_json::to_string(self).expect("panic reports always serialize")
+    }
+}
+
+/// A snapshot of what a panic happened on, for telling apart bugs specific to
+/// a browser or platform. See
+/// [`HookBuilder::with_environment`](struct.HookBuilder.html#method.with_environment).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct Environment {
+    pub(crate) user_agent: Option<String>,
+    pub(crate) platform: Option<String>,
+    pub(crate) hardware_concurrency: Option<u32>,
+}
+
+impl Environment {
+    /// `navigator.userAgent`, if there is a `navigator`.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_ref().map(|s| &s[..])
+    }
+
+    /// The platform, such as `macOS` or `Windows`. In JavaScript this comes
+    /// from the User-Agent Client Hints' `navigator.userAgentData.platform`
+    /// where available, and `navigator.platform` elsewhere. On non-wasm
+    /// targets it is the operating system, as in `std::env::consts::OS`.
+    pub fn platform(&self) -> Option<&str> {
+        self.platform.as_ref().map(|s| &s[..])
+    }
+
+    /// How many threads can run in parallel, from
+    /// `navigator.hardwareConcurrency` or, on non-wasm targets,
+    /// `std::thread::available_parallelism`.
+    pub fn hardware_concurrency(&self) -> Option<u32> {
+        self.hardware_concurrency
+    }
+}
+
+/// What was built and deployed, to match panics from the field to the exact
+/// binary they came from. See
+/// [`HookBuilder::with_build_info`](struct.HookBuilder.html#method.with_build_info).
+///
+/// ```
+/// use console_error_panic_hook::BuildInfo;
+///
+/// let mut build = BuildInfo::new(env!("CARGO_PKG_VERSION"));
+/// if let Some(hash) = option_env!("GIT_HASH") {
+///     build = build.with_git_hash(hash);
+/// }
+/// if let Some(time) = option_env!("BUILD_TIME") {
+///     build = build.with_build_time(time);
+/// }
+///
+/// console_error_panic_hook::builder()
+///     .with_build_info(build)
+///     .install();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct BuildInfo {
+    pub(crate) version: String,
+    pub(crate) git_hash: Option<String>,
+    pub(crate) build_time: Option<String>,
+}
+
+impl BuildInfo {
+    /// Build info for `version`, typically `env!("CARGO_PKG_VERSION")`.
+    pub fn new(version: &str) -> BuildInfo {
+        BuildInfo {
+            version: version.to_string(),
+            git_hash: None,
+            build_time: None,
+        }
+    }
+
+    /// The commit that was built.
+    pub fn with_git_hash(mut self, git_hash: &str) -> BuildInfo {
+        self.git_hash = Some(git_hash.to_string());
+        self
+    }
+
+    /// When it was built, in whatever form the build script supplies.
+    pub fn with_build_time(mut self, build_time: &str) -> BuildInfo {
+        self.build_time = Some(build_time.to_string());
+        self
+    }
+
+    /// The version that was built.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// The commit that was built, if known.
+    pub fn git_hash(&self) -> Option<&str> {
+        self.git_hash.as_ref().map(|s| &s[..])
+    }
+
+    /// When it was built, if known.
+    pub fn build_time(&self) -> Option<&str> {
+        self.build_time.as_ref().map(|s| &s[..])
+    }
+}
+
+/// The "Build: ..." section of `report`'s message, if it has build info.
+pub(crate) fn build_section(report: &PanicReport) -> Option<String> {
+    report.build.as_ref().map(|build| {
+        let mut section = format!("Build: {}", build.version);
+        if let Some(ref git_hash) = build.git_hash {
+            section.push_str(&format!(" ({})", git_hash));
+        }
+        if let Some(ref build_time) = build.build_time {
+            section.push_str(&format!(", built {}", build_time));
+        }
+        section
+    })
+}
+
+/// The "Time: ..." section of `report`'s message, with its timestamp as an
+/// ISO 8601 UTC date and its uptime, whichever are known.
+pub(crate) fn time_section(report: &PanicReport) -> Option<String> {
+    let uptime = report
+        .uptime
+        .map(|uptime| format!("{:.1}ms after start", uptime));
+    match (report.timestamp, uptime) {
+        (Some(timestamp), Some(uptime)) => {
+            Some(format!("Time: {} ({})", iso8601(timestamp), uptime))
+        }
+        (Some(timestamp), None) => Some(format!("Time: {}", iso8601(timestamp))),
+        (None, Some(uptime)) => Some(format!("Time: {}", uptime)),
+        (None, None) => None,
+    }
+}
+
+/// The "Memory: ..." section of `report`'s message, if its memory size is
+/// known.
+pub(crate) fn memory_section(report: &PanicReport) -> Option<String> {
+    report.memory.map(|bytes| {
+        format!(
+            "Memory: {} pages ({:.1} MiB)",
+            bytes / WASM_PAGE_SIZE,
+            bytes as f64 / (1024.0 * 1024.0)
+        )
+    })
+}
+
+/// The "Context:" section of `report`'s message, with a line for each of its
+/// contexts, if it has any.
+pub(crate) fn context_section(report: &PanicReport) -> Option<String> {
+    if report.context.is_empty() {
+        return None;
+    }
+    let mut section = "Context:".to_string();
+    for context in &report.context {
+        section.push_str("\n  ");
+        section.push_str(context);
+    }
+    Some(section)
+}
+
+/// The "State:" section of `report`'s message, with a line for each of its
+/// key-value pairs, if it has any.
+pub(crate) fn state_section(report: &PanicReport) -> Option<String> {
+    if report.state.is_empty() {
+        return None;
+    }
+    let mut section = "State:".to_string();
+    for (key, value) in &report.state {
+        section.push_str(&format!("\n  {}: {}", key, value));
+    }
+    Some(section)
+}
+
+/// The "Breadcrumbs:" section of `report`'s message, with a line for each of
+/// its breadcrumbs, if it has any.
+pub(crate) fn breadcrumbs_section(report: &PanicReport) -> Option<String> {
+    if report.breadcrumbs.is_empty() {
+        return None;
+    }
+    let mut section = "Breadcrumbs:".to_string();
+    for breadcrumb in &report.breadcrumbs {
+        section.push_str("\n  ");
+        if let Some(timestamp) = breadcrumb.timestamp {
+            section.push_str(&iso8601(timestamp));
+            section.push(' ');
+        }
+        section.push_str(&format!("[{}] {}", breadcrumb.category, breadcrumb.message));
+    }
+    Some(section)
+}
+
+/// The size of a wasm page, in bytes.
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
+/// The current size of linear memory, in bytes, on `wasm32`.
+pub(crate) fn memory_size() -> Option<u64> {
+    #[cfg(target_arch = "wasm32")]
+    {
+        Some(core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        None
+    }
+}
+
+/// `timestamp`, in milliseconds since the Unix epoch, as an ISO 8601 UTC date
+/// like `2024-01-31T12:34:56.789Z`.
+pub(crate) fn iso8601(timestamp: f64) -> String {
+    let millis = timestamp as i64;
+    let days = millis.div_euclid(86_400_000);
+    let millis = millis.rem_euclid(86_400_000);
+
+    // Howard Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// The current thread's name, or its id if it is unnamed.
+pub(crate) fn current_thread() -> String {
+    let thread = thread::current();
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+/// The panic's payload, if it is a string (which it is for any `panic!` with
+/// a message).
+pub(crate) fn payload_str<'a>(info: &'a PanicHookInfo) -> Option<&'a str> {
+    downcast_str(info.payload())
+}
+
+fn downcast_str(payload: &(dyn Any + Send)) -> Option<&str> {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => Some(s),
+        None => payload.downcast_ref::<String>().map(|s| &s[..]),
+    }
+}
+
+/// Somewhere to deliver panic reports to.
+///
+/// Implement this to send panics to a crash reporting backend, and install it
+/// with [`HookBuilder::with_reporter`](struct.HookBuilder.html#method.with_reporter)
+/// or [`set_once_with_reporter`](fn.set_once_with_reporter.html).
+///
+/// ```
+/// use console_error_panic_hook::{PanicReport, Reporter};
+///
+/// struct MyBackend;
+///
+/// impl Reporter for MyBackend {
+///     fn report(&self, report: &PanicReport) {
+///         // Send `report.message()` somewhere...
+///     }
+/// }
+///
+/// console_error_panic_hook::set_once_with_reporter(Box::new(MyBackend));
+/// ```
+pub trait Reporter: Send + Sync {
+    /// Deliver the given panic report.
+    fn report(&self, report: &PanicReport);
+
+    /// Whether this sends panics to a remote service, which
+    /// [`HookBuilder::with_runtime_config`](struct.HookBuilder.html#method.with_runtime_config)
+    /// lets them be kept from. Defaults to `false`.
+    fn is_remote(&self) -> bool {
+        false
+    }
+}
+
+/// Reporters can be shared, to keep a handle on one after installing it.
+impl<R: Reporter + ?Sized> Reporter for Arc<R> {
+    fn report(&self, report: &PanicReport) {
+        (**self).report(report);
+    }
+
+    fn is_remote(&self) -> bool {
+        (**self).is_remote()
+    }
+}
+
+/// A reporter that hands each panic's formatted message to a closure.
+///
+/// Create one with `FnReporter::new`, or use
+/// [`set_once_with`](fn.set_once_with.html) directly.
+pub struct FnReporter<F> {
+    f: F,
+}
+
+impl<F> FnReporter<F>
+where
+    F: Fn(&str) + Send + Sync,
+{
+    /// Create a reporter that calls `f` with each panic's formatted message.
+    pub fn new(f: F) -> FnReporter<F> {
+        FnReporter { f }
+    }
+}
+
+impl<F> Reporter for FnReporter<F>
+where
+    F: Fn(&str) + Send + Sync,
+{
+    fn report(&self, report: &PanicReport) {
+        (self.f)(report.message());
+    }
+}
+
+/// A reporter that writes each panic's formatted message, followed by a
+/// newline, to any `io::Write`: a log file, a pipe to a supervisor, and so on.
+///
+/// This is mostly useful on non-wasm targets, where the default is to write to
+/// `stderr`.
+///
+/// ```no_run
+/// use console_error_panic_hook::WriterReporter;
+/// use std::fs::File;
+///
+/// let log = File::create("panics.log").unwrap();
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(WriterReporter::new(Box::new(log))))
+///     .install();
+/// ```
+pub struct WriterReporter {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl WriterReporter {
+    /// Create a reporter that writes panics to `writer`.
+    pub fn new(writer: Box<dyn Write + Send>) -> WriterReporter {
+        WriterReporter {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl fmt::Debug for WriterReporter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WriterReporter").finish_non_exhaustive()
+    }
+}
+
+impl Reporter for WriterReporter {
+    fn report(&self, report: &PanicReport) {
+        let mut writer = lock(&self.writer);
+        // There is nowhere left to report a failure to report a panic.
+        let _ = writeln!(writer, "{}", report.message());
+        let _ = writer.flush();
+    }
+}
+
+/// A reporter that logs each panic as a single line of JSON, with
+/// `console.error` or to `stderr` on non-wasm targets.
+///
+/// Requires the `json` feature.
+#[cfg(feature = "json")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonReporter;
+
+#[cfg(feature = "json")]
+impl Reporter for JsonReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::console_error(&report.to_json());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_iso8601_dates() {
+        assert_eq!(iso8601(0.0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(iso8601(951_782_400_123.0), "2000-02-29T00:00:00.123Z");
+        assert_eq!(iso8601(1_706_704_496_789.0), "2024-01-31T12:34:56.789Z");
+        assert_eq!(iso8601(-1.0), "1969-12-31T23:59:59.999Z");
+    }
+}
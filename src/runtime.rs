@@ -0,0 +1,43 @@
+use super::{imp, ConsoleReporter};
+
+/// The environment the module runs in, which decides how panics are reported
+/// by default.
+///
+/// Hooks detect this when they are installed, unless it is given with
+/// [`HookBuilder::with_runtime`](struct.HookBuilder.html#method.with_runtime).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Runtime {
+    /// A browser's main thread, with a `window`.
+    Browser,
+    /// A Web Worker of any kind.
+    Worker,
+    /// Node.js.
+    Node,
+    /// Deno.
+    Deno,
+    /// A native target, or WASI.
+    Native,
+    /// Some other embedder, or one that can't be told apart.
+    Unknown,
+}
+
+impl Runtime {
+    /// Work out which runtime this is.
+    pub fn detect() -> Runtime {
+        imp::detect_runtime()
+    }
+
+    /// The reporter to use when none were configured.
+    pub(crate) fn default_reporter(self) -> ConsoleReporter {
+        match self {
+            // Node's `console.error` is asynchronous when `stderr` is a pipe;
+            // a synchronous write makes sure the panic is seen.
+            Runtime::Node => ConsoleReporter::new().with_node_stderr(true),
+            Runtime::Browser
+            | Runtime::Worker
+            | Runtime::Deno
+            | Runtime::Native
+            | Runtime::Unknown => ConsoleReporter::new(),
+        }
+    }
+}
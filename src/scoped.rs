@@ -0,0 +1,43 @@
+use std::fmt;
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use super::Hook;
+
+/// How many `ScopedHook`s are currently alive.
+pub(crate) static ACTIVE_SCOPES: AtomicUsize = AtomicUsize::new(0);
+
+/// A guard that keeps a panic hook installed for as long as it is alive, and
+/// restores the previously installed hook when it is dropped.
+///
+/// Create one with [`scoped`](fn.scoped.html) or
+/// [`HookBuilder::install_scoped`](struct.HookBuilder.html#method.install_scoped).
+#[must_use = "the previous panic hook is restored as soon as the guard is dropped"]
+pub struct ScopedHook {
+    previous: Option<Arc<Hook>>,
+}
+
+impl ScopedHook {
+    pub(crate) fn new(previous: Arc<Hook>) -> ScopedHook {
+        ACTIVE_SCOPES.fetch_add(1, Ordering::SeqCst);
+        ScopedHook {
+            previous: Some(previous),
+        }
+    }
+}
+
+impl fmt::Debug for ScopedHook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScopedHook").finish()
+    }
+}
+
+impl Drop for ScopedHook {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            panic::set_hook(Box::new(move |info| previous(info)));
+        }
+        ACTIVE_SCOPES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
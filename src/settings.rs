@@ -0,0 +1,49 @@
+use super::Verbosity;
+
+/// Overrides for a hook's configuration, read when it is installed. See
+/// [`HookBuilder::with_runtime_config`](struct.HookBuilder.html#method.with_runtime_config).
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Settings {
+    pub(crate) verbosity: Option<Verbosity>,
+    pub(crate) prefix: Option<String>,
+    pub(crate) remote: Option<bool>,
+}
+
+impl Settings {
+    /// Apply `list`, a comma-separated list of settings such as
+    /// `verbose,no-remote`. Anything not understood is ignored.
+    pub(crate) fn apply_list(&mut self, list: &str) {
+        for setting in list.split(',') {
+            match setting.trim() {
+                "short" | "quiet" => self.verbosity = Some(Verbosity::Short),
+                "full" | "verbose" => self.verbosity = Some(Verbosity::Full),
+                "remote" => self.remote = Some(true),
+                "no-remote" => self.remote = Some(false),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_lists() {
+        let mut settings = Settings::default();
+        settings.apply_list("verbose, no-remote");
+        assert_eq!(
+            settings,
+            Settings {
+                verbosity: Some(Verbosity::Full),
+                prefix: None,
+                remote: Some(false),
+            }
+        );
+
+        settings.apply_list("short,bogus");
+        assert_eq!(settings.verbosity, Some(Verbosity::Short));
+        assert_eq!(settings.remote, Some(false));
+    }
+}
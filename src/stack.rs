@@ -0,0 +1,415 @@
+//! Post-processing for captured stack traces.
+
+use builder::Config;
+#[cfg(feature = "dwarf")]
+use dwarf::DebugInfo;
+use names::FunctionNames;
+
+/// Substrings identifying stack frames that belong to this hook or the panic
+/// machinery that calls it, in both demangled and mangled form.
+///
+/// Note that this can't include all of `std::panicking`, since
+/// `std::panicking::catch_unwind` and friends show up below the panicking
+/// frame.
+const HOOK_FRAMES: &[&str] = &[
+    "console_error_panic_hook",
+    "rust_panic",
+    "begin_panic",
+    "rust_begin_unwind",
+    "__rust_end_short_backtrace",
+    "core::panicking",
+    "4core9panicking",
+];
+
+/// Substrings identifying frames of this crate that show up *below* the
+/// panicking frame, because they call the code that panicked, in both
+/// demangled and mangled form. These aren't trimmed with our other frames.
+const CALLER_FRAMES: &[&str] = &[
+    "console_error_panic_hook::catch::",
+    "24console_error_panic_hook5catch",
+];
+
+/// Substrings identifying stack frames that belong to `wasm-bindgen`'s
+/// generated glue, in both demangled and mangled form.
+const GLUE_FRAMES: &[&str] = &["__wbg_", "__wbindgen", "wasm_bindgen::", "12wasm_bindgen"];
+
+/// Apply every configured pass to a captured stack.
+pub(crate) fn process(config: &Config, stack: String) -> String {
+    let stack = match config.function_names {
+        Some(ref names) => symbolicate(names, &stack),
+        None => stack,
+    };
+
+    #[cfg(feature = "dwarf")]
+    let stack = match config.debug_info {
+        Some(ref debug_info) => resolve_locations(debug_info, &stack),
+        None => stack,
+    };
+
+    #[cfg(feature = "demangle")]
+    let stack = demangle(&stack);
+
+    let stack = if config.keep_hook_frames {
+        stack
+    } else {
+        trim_hook_frames(&stack)
+    };
+
+    let stack = if config.keep_glue_frames {
+        stack
+    } else {
+        drop_glue_frames(&stack)
+    };
+
+    match config.max_frames {
+        Some(max_frames) => truncate(&stack, max_frames),
+        None => stack,
+    }
+}
+
+/// Keep only the first `max_frames` frames, noting how many were dropped.
+fn truncate(stack: &str, max_frames: usize) -> String {
+    let frames = frames(stack);
+    if frames.len() <= max_frames {
+        return stack.to_string();
+    }
+    let mut truncated = frames[..max_frames].join("\n");
+    if max_frames > 0 {
+        truncated.push('\n');
+    }
+    let dropped = frames.len() - max_frames;
+    let plural = if dropped == 1 { "" } else { "s" };
+    truncated.push_str(&format!("… {} more frame{}", dropped, plural));
+    truncated
+}
+
+/// Name the `wasm-function[1234]` frames whose names we know.
+///
+/// Engines write unnamed frames either with the function as the frame's name,
+/// like Firefox's `wasm-function[1234]@app_bg.wasm:wasm-function[1234]:0x5678`,
+/// or with only a location, like V8's `at app_bg.wasm:wasm-function[1234]:0x5678`.
+/// The former has its name replaced, the latter gets a name the same way V8
+/// writes named frames.
+fn symbolicate(names: &FunctionNames, stack: &str) -> String {
+    const MARKER: &str = "wasm-function[";
+
+    stack
+        .lines()
+        .map(|line| {
+            let start = match line.find(MARKER) {
+                Some(start) => start,
+                None => return line.to_string(),
+            };
+            let index_start = start + MARKER.len();
+            let index_end = match line[index_start..].find(']') {
+                Some(len) => index_start + len,
+                None => return line.to_string(),
+            };
+            let name = match line[index_start..index_end]
+                .parse()
+                .ok()
+                .and_then(|index| names.get(index))
+            {
+                Some(name) => name,
+                None => return line.to_string(),
+            };
+
+            if !line[..start].ends_with(':') {
+                return format!("{}{}{}", &line[..start], name, &line[index_end + 1..]);
+            }
+            let indent = line.len() - line.trim_start().len();
+            match line[indent..].strip_prefix("at ") {
+                Some(location) => format!("{}at {} ({})", &line[..indent], name, location),
+                None => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The offset from the start of the module of a wasm frame, which engines
+/// write as `wasm-function[1234]:0x5678`.
+#[cfg_attr(not(any(feature = "dwarf", test)), allow(dead_code))]
+fn wasm_offset(line: &str) -> Option<u64> {
+    let start = line.find("wasm-function[")?;
+    let hex = &line[start..];
+    let hex = &hex[hex.find("]:0x")? + "]:0x".len()..];
+    let end = hex
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(hex.len());
+    u64::from_str_radix(&hex[..end], 16).ok()
+}
+
+/// Append the Rust source location to every wasm frame DWARF knows about.
+#[cfg(feature = "dwarf")]
+fn resolve_locations(debug_info: &DebugInfo, stack: &str) -> String {
+    let resolver = match debug_info.resolver() {
+        Some(resolver) => resolver,
+        None => return stack.to_string(),
+    };
+    stack
+        .lines()
+        .map(
+            |line| match wasm_offset(line).and_then(|offset| resolver.location(offset)) {
+                Some(location) => format!("{} [{}]", line, location),
+                None => line.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drop every frame from `wasm-bindgen`'s glue, which otherwise hides the
+/// actual chain of Rust calls.
+fn drop_glue_frames(stack: &str) -> String {
+    frames(stack)
+        .into_iter()
+        .filter(|line| !GLUE_FRAMES.iter().any(|frame| line.contains(frame)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Rewrite every mangled Rust symbol in the stack, such as
+/// `_ZN4core9panicking5panic17h0123456789abcdefE`, into its readable form
+/// without the hash, such as `core::panicking::panic`.
+#[cfg(feature = "demangle")]
+fn demangle(stack: &str) -> String {
+    use rustc_demangle::try_demangle;
+
+    let is_symbol_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.';
+
+    let mut demangled = String::with_capacity(stack.len());
+    let mut rest = stack;
+    while let Some(start) = rest.find(is_symbol_char) {
+        demangled.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_symbol_char(c)).unwrap_or(rest.len());
+        let token = &rest[..end];
+        rest = &rest[end..];
+
+        // Engines sometimes put a `$` or module name in front of the symbol.
+        let symbol_start = token.find("_ZN").or_else(|| {
+            if token.starts_with("_R") {
+                Some(0)
+            } else {
+                None
+            }
+        });
+        match symbol_start.map(|i| (i, try_demangle(&token[i..]))) {
+            Some((i, Ok(symbol))) => {
+                demangled.push_str(&token[..i]);
+                demangled.push_str(&format!("{:#}", symbol));
+            }
+            _ => demangled.push_str(token),
+        }
+    }
+    demangled.push_str(rest);
+    demangled
+}
+
+/// Drop the frames above the panicking frame: the JavaScript glue capturing the
+/// stack, this hook, and the standard library's panic machinery.
+///
+/// V8 can do this itself with `Error.captureStackTrace`, but it needs a
+/// JavaScript function to cut at and every frame we want gone is a wasm
+/// function, so we slice the text instead, which works in every engine. If no
+/// frame is recognized (for example because the name section was stripped),
+/// the stack is left alone. So it is if every frame is ours, which happens
+/// when `Error.stackTraceLimit` cuts the stack off before the panicking frame:
+/// an empty stack would hide why.
+fn trim_hook_frames(stack: &str) -> String {
+    let frames = frames(stack);
+    match frames.iter().rposition(|line| is_hook_frame(line)) {
+        Some(last) if last + 1 < frames.len() => frames[last + 1..].join("\n"),
+        _ => stack.to_string(),
+    }
+}
+
+fn is_hook_frame(frame: &str) -> bool {
+    HOOK_FRAMES.iter().any(|hook| frame.contains(hook))
+        && !CALLER_FRAMES.iter().any(|caller| frame.contains(caller))
+}
+
+/// Split a stack into its frames. Native backtraces list each frame's source
+/// location on the lines after its name, so those lines stay with the frame.
+fn frames(stack: &str) -> Vec<&str> {
+    let native = stack.lines().next().is_some_and(is_native_frame);
+
+    let mut frames = Vec::new();
+    let mut start = 0;
+    let mut offset = 0;
+    for line in stack.split('\n') {
+        if offset > start && (!native || is_native_frame(line)) {
+            frames.push(&stack[start..offset - 1]);
+            start = offset;
+        }
+        offset += line.len() + 1;
+    }
+    if start < stack.len() {
+        frames.push(&stack[start..]);
+    }
+    frames
+}
+
+/// Whether `line` starts a frame of a native backtrace, like `  12: app::main`.
+fn is_native_frame(line: &str) -> bool {
+    let line = line.trim_start();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    digits > 0 && line[digits..].starts_with(':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{drop_glue_frames, symbolicate, trim_hook_frames, truncate, wasm_offset};
+    use names::FunctionNames;
+
+    #[test]
+    fn trims_frames_up_to_the_panicking_frame() {
+        let stack = "Error
+    at imports.wbg.__wbg_new_abcd (http://localhost/pkg/app.js:10:21)
+    at console_error_panic_hook::hook::h1234 (http://localhost/pkg/app_bg.wasm:wasm-function[10]:0x100)
+    at std::panicking::rust_panic_with_hook::h5678 (http://localhost/pkg/app_bg.wasm:wasm-function[20]:0x200)
+    at core::panicking::panic_fmt::h9abc (http://localhost/pkg/app_bg.wasm:wasm-function[30]:0x300)
+    at app::main::hdef0 (http://localhost/pkg/app_bg.wasm:wasm-function[40]:0x400)
+    at app::start (http://localhost/pkg/app_bg.wasm:wasm-function[50]:0x500)";
+        assert_eq!(
+            trim_hook_frames(stack),
+            "    at app::main::hdef0 (http://localhost/pkg/app_bg.wasm:wasm-function[40]:0x400)
+    at app::start (http://localhost/pkg/app_bg.wasm:wasm-function[50]:0x500)"
+        );
+    }
+
+    #[test]
+    fn drops_glue_frames() {
+        let stack = "    at app::handler::h1234 (app_bg.wasm:wasm-function[40]:0x400)
+    at wasm_bindgen::convert::closures::invoke0_mut::h5678 (app_bg.wasm:wasm-function[60]:0x600)
+    at __wbg_adapter_22 (http://localhost/pkg/app.js:231:10)
+    at real (http://localhost/pkg/app.js:200:20)";
+        assert_eq!(
+            drop_glue_frames(stack),
+            "    at app::handler::h1234 (app_bg.wasm:wasm-function[40]:0x400)
+    at real (http://localhost/pkg/app.js:200:20)"
+        );
+    }
+
+    #[test]
+    fn names_unnamed_wasm_frames() {
+        let names: FunctionNames = vec![(40, "app::handler"), (50, "app::start")]
+            .into_iter()
+            .collect();
+        let stack = "wasm-function[40]@http://localhost/app_bg.wasm:wasm-function[40]:0x400
+    at http://localhost/app_bg.wasm:wasm-function[50]:0x500
+    at http://localhost/app_bg.wasm:wasm-function[60]:0x600";
+        assert_eq!(
+            symbolicate(&names, stack),
+            "app::handler@http://localhost/app_bg.wasm:wasm-function[40]:0x400
+    at app::start (http://localhost/app_bg.wasm:wasm-function[50]:0x500)
+    at http://localhost/app_bg.wasm:wasm-function[60]:0x600"
+        );
+    }
+
+    #[test]
+    fn truncates_to_max_frames() {
+        let stack = "    at a (app.js:1:1)\n    at b (app.js:2:1)\n    at c (app.js:3:1)";
+        assert_eq!(truncate(stack, 3), stack);
+        assert_eq!(truncate(stack, 1), "    at a (app.js:1:1)\n… 2 more frames");
+        assert_eq!(truncate(stack, 0), "… 3 more frames");
+    }
+
+    #[test]
+    fn finds_wasm_frame_offsets() {
+        assert_eq!(
+            wasm_offset("    at app::start (http://localhost/app_bg.wasm:wasm-function[50]:0x5a0)"),
+            Some(0x5a0)
+        );
+        assert_eq!(
+            wasm_offset("wasm-function[50]@http://localhost/app_bg.wasm:wasm-function[50]:0x5a0"),
+            Some(0x5a0)
+        );
+        assert_eq!(
+            wasm_offset("    at real (http://localhost/pkg/app.js:200:20)"),
+            None
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "demangle")]
+    fn demangles_rust_symbols() {
+        use super::demangle;
+
+        let stack = "    at $_ZN4core9panicking5panic17h0123456789abcdefE (app_bg.wasm:wasm-function[30]:0x300)
+    at app::start (app_bg.wasm:wasm-function[50]:0x500)";
+        assert_eq!(
+            demangle(stack),
+            "    at $core::panicking::panic (app_bg.wasm:wasm-function[30]:0x300)
+    at app::start (app_bg.wasm:wasm-function[50]:0x500)"
+        );
+    }
+
+    #[test]
+    fn trims_native_backtraces() {
+        let stack = "   0: std::backtrace::Backtrace::force_capture
+             at /rustc/library/std/src/backtrace.rs:312:9
+   1: std::panicking::rust_panic_with_hook
+             at /rustc/library/std/src/panicking.rs:841:13
+   2: core::panicking::panic_fmt
+             at /rustc/library/core/src/panicking.rs:75:14
+   3: app::main
+             at ./src/main.rs:3:5
+   4: std::panicking::catch_unwind
+             at /rustc/library/std/src/panicking.rs:544:19";
+        assert_eq!(
+            trim_hook_frames(stack),
+            "   3: app::main
+             at ./src/main.rs:3:5
+   4: std::panicking::catch_unwind
+             at /rustc/library/std/src/panicking.rs:544:19"
+        );
+        assert_eq!(
+            truncate(&trim_hook_frames(stack), 1),
+            "   3: app::main
+             at ./src/main.rs:3:5
+… 1 more frame"
+        );
+    }
+
+    #[test]
+    fn keeps_the_panicking_frame_under_catch_and_report() {
+        let stack = "   0: std::backtrace::Backtrace::force_capture
+             at /rustc/library/std/src/backtrace.rs:312:9
+   1: console_error_panic_hook::hook_impl
+             at ./src/lib.rs:900:5
+   2: core::panicking::panic_fmt
+             at /rustc/library/core/src/panicking.rs:75:14
+   3: app::load::{{closure}}
+             at ./src/main.rs:3:5
+   4: console_error_panic_hook::catch::catch_and_report::{{closure}}
+             at ./src/catch.rs:60:9
+   5: std::panicking::catch_unwind
+             at /rustc/library/std/src/panicking.rs:544:19";
+        assert_eq!(
+            trim_hook_frames(stack),
+            "   3: app::load::{{closure}}
+             at ./src/main.rs:3:5
+   4: console_error_panic_hook::catch::catch_and_report::{{closure}}
+             at ./src/catch.rs:60:9
+   5: std::panicking::catch_unwind
+             at /rustc/library/std/src/panicking.rs:544:19"
+        );
+    }
+
+    #[test]
+    fn leaves_stacks_of_only_hook_frames_alone() {
+        let stack = "Error
+    at __wbg_new_0d7da8e129c00c84 (http://localhost/pkg/app.js:300:13)
+    at app.wasm.console_error_panic_hook::hook_impl::h1 (http://localhost/pkg/app_bg.wasm:wasm-function[20]:0x200)";
+        assert_eq!(trim_hook_frames(stack), stack);
+    }
+
+    #[test]
+    fn leaves_unrecognized_stacks_alone() {
+        let stack = "wasm-function[10]@http://localhost/pkg/app_bg.wasm:wasm-function[10]:0x100
+wasm-function[40]@http://localhost/pkg/app_bg.wasm:wasm-function[40]:0x400";
+        assert_eq!(trim_hook_frames(stack), stack);
+    }
+}
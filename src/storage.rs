@@ -0,0 +1,61 @@
+use super::{imp, PanicReport, Reporter};
+
+/// The key panics are stored under unless configured otherwise.
+const DEFAULT_KEY: &str = "console_error_panic_hook:last_panic";
+
+/// A reporter that stores the most recent panic in `sessionStorage`, where it
+/// survives the page being reloaded and the console cleared.
+///
+/// The panic is stored as JSON, as an object with the `message`, `payload`,
+/// `file`, `line`, `column`, `thread`, `stack`, `timestamp`, `uptime`,
+/// `memory`, `environment`, `build`, and `moduleName` of the
+/// [`PanicReport`](struct.PanicReport.html), under the key
+/// `console_error_panic_hook:last_panic` unless set with
+/// [`with_key`](#method.with_key). After a reload, read it with:
+///
+/// ```js
+/// const lastPanic = JSON.parse(sessionStorage.getItem("console_error_panic_hook:last_panic"));
+/// ```
+///
+/// Where there is no `sessionStorage`, or it is full or blocked, and on
+/// non-wasm targets, this does nothing.
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, StorageReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(StorageReporter::new()))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct StorageReporter {
+    key: String,
+}
+
+impl StorageReporter {
+    /// Create a reporter that stores panics under the default key.
+    pub fn new() -> StorageReporter {
+        StorageReporter {
+            key: DEFAULT_KEY.to_string(),
+        }
+    }
+
+    /// Store panics under `key` instead of the default one.
+    pub fn with_key<S: Into<String>>(mut self, key: S) -> StorageReporter {
+        self.key = key.into();
+        self
+    }
+}
+
+impl Default for StorageReporter {
+    fn default() -> StorageReporter {
+        StorageReporter::new()
+    }
+}
+
+impl Reporter for StorageReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::store_in_session(report, &self.key);
+    }
+}
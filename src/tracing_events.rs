@@ -0,0 +1,138 @@
+use super::{PanicReport, Reporter};
+
+/// A reporter that emits panics as `tracing` events, at `ERROR` level, with
+/// the target `panic`, so that subscribers such as `tracing-wasm` capture them
+/// along with the rest of the application's telemetry. Requires the `tracing`
+/// feature.
+///
+/// The event's message is the panic's message, and its `file`, `line`,
+/// `column`, `thread`, and `payload` fields are the panic's. Add a
+/// [`ConsoleReporter`](struct.ConsoleReporter.html) too to keep logging
+/// panics directly, in case no subscriber is set up yet:
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, TracingReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(TracingReporter::new()))
+///     .install();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TracingReporter;
+
+impl TracingReporter {
+    /// Create a reporter that emits panics as `tracing` events.
+    pub fn new() -> TracingReporter {
+        TracingReporter
+    }
+}
+
+impl Reporter for TracingReporter {
+    fn report(&self, report: &PanicReport) {
+        tracing::error!(
+            target: "panic",
+            file = report.file(),
+            line = report.line(),
+            column = report.column(),
+            thread = report.thread(),
+            payload = report.payload(),
+            "{}",
+            report.message(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{subscriber, Event, Level, Metadata, Subscriber};
+
+    use super::*;
+
+    /// The fields of each event.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<Fields>>>);
+
+    /// An event's fields, by name.
+    #[derive(Default)]
+    struct Fields(Vec<(String, String)>);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            *metadata.level() == Level::ERROR && metadata.target() == "panic"
+        }
+
+        fn new_span(&self, _span: &Attributes) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut fields = Fields::default();
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn emits_panics_with_fields() {
+        let recorder = Recorder::default();
+        subscriber::with_default(recorder.clone(), || {
+            TracingReporter::new().report(&PanicReport {
+                message: "panicked at src/main.rs:3:5:\nboom".to_string(),
+                payload: Some("boom".to_string()),
+                file: Some("src/main.rs".to_string()),
+                line: Some(3),
+                column: Some(5),
+                thread: "main".to_string(),
+                timestamp: None,
+                uptime: None,
+                memory: None,
+                environment: None,
+                build: None,
+                breadcrumbs: Vec::new(),
+                context: Vec::new(),
+                state: Vec::new(),
+                module_name: None,
+                stack: None,
+                source_link: None,
+            });
+        });
+
+        let events = recorder.0.lock().unwrap();
+        let field = |name: &str| {
+            events[0]
+                .0
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(field("message"), Some("panicked at src/main.rs:3:5:\nboom"));
+        assert_eq!(field("file"), Some("src/main.rs"));
+        assert_eq!(field("line"), Some("3"));
+        assert_eq!(field("payload"), Some("boom"));
+    }
+}
@@ -0,0 +1,196 @@
+use std::sync::Mutex;
+
+use super::buffer::RingBuffer;
+use super::{imp, lock, PanicReport, Reporter};
+
+/// How long to wait before the first retry, in milliseconds.
+const INITIAL_BACKOFF: f64 = 1000.0;
+
+/// The longest to wait between retries, in milliseconds.
+const MAX_BACKOFF: f64 = 60_000.0;
+
+/// A way of getting panic reports to a remote service: serialize a batch of
+/// them into a payload, then deliver it.
+///
+/// [`BeaconReporter`](struct.BeaconReporter.html),
+/// [`FetchReporter`](struct.FetchReporter.html),
+/// [`WebSocketReporter`](struct.WebSocketReporter.html), and
+/// [`SentryReporter`](struct.SentryReporter.html) are all transports. As
+/// reporters, they send each panic right away, once. Wrap one, or a transport
+/// of your own, in a [`TransportReporter`](struct.TransportReporter.html) to
+/// batch reports and retry failed deliveries.
+///
+/// ```
+/// use console_error_panic_hook::{PanicReport, Transport, TransportReporter};
+///
+/// struct MyService;
+///
+/// impl Transport for MyService {
+///     fn serialize(&self, reports: &[PanicReport]) -> Option<String> {
+///         let messages: Vec<_> = reports.iter().map(|report| report.message()).collect();
+///         Some(messages.join("\n---\n"))
+///     }
+///
+///     fn deliver(&self, payload: &str) -> bool {
+///         // Send `payload` somewhere, and say whether that worked...
+///         true
+///     }
+/// }
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(TransportReporter::new(MyService)))
+///     .install();
+/// ```
+pub trait Transport: Send + Sync {
+    /// Serialize the reports, oldest first, into a single payload. Returns
+    /// `None` if they can't be sent, for example because they are too large,
+    /// in which case they are dropped.
+    fn serialize(&self, reports: &[PanicReport]) -> Option<String>;
+
+    /// Deliver a payload, returning whether it was handed off. Returning
+    /// `false` has it retried later.
+    fn deliver(&self, payload: &str) -> bool;
+
+    /// The most reports to serialize into one payload. Defaults to no limit.
+    fn max_batch(&self) -> usize {
+        usize::MAX
+    }
+}
+
+/// Deliver the payload for a single report with `transport`, once, unless
+/// it couldn't be serialized.
+pub(crate) fn send<T: Transport + ?Sized>(transport: &T, payload: Option<String>) {
+    if let Some(payload) = payload {
+        transport.deliver(&payload);
+    }
+}
+
+/// A reporter that queues panics and sends them in batches with a
+/// [`Transport`](trait.Transport.html), retrying failed deliveries with
+/// exponential backoff.
+///
+/// Each panic is queued, and the queue is flushed right away unless a retry
+/// is due later. Deliveries are retried as new panics come in, or on
+/// [`flush`](#method.flush), waiting twice as long after each failure, up to
+/// a minute; a batch is dropped once it has failed
+/// [`with_max_retries`](#method.with_max_retries) times. Share the reporter
+/// through an `Arc` to flush it from elsewhere, say when the page is hidden:
+///
+/// ```
+/// use console_error_panic_hook::{BeaconReporter, TransportReporter};
+/// use std::sync::Arc;
+///
+/// let reporter = Arc::new(TransportReporter::new(BeaconReporter::new(
+///     "https://example.com/panics",
+/// )));
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(reporter.clone()))
+///     .install();
+///
+/// // Later, from a `visibilitychange` listener:
+/// reporter.flush();
+/// ```
+#[derive(Debug)]
+pub struct TransportReporter<T> {
+    transport: T,
+    capacity: usize,
+    max_batch: usize,
+    max_retries: u32,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    queue: RingBuffer,
+    failures: u32,
+    retry_at: f64,
+}
+
+impl<T: Transport> TransportReporter<T> {
+    /// Create a reporter that sends panics with `transport`, keeping up to 32
+    /// of them queued, sending up to 16 at a time, and retrying each batch up
+    /// to 5 times.
+    pub fn new(transport: T) -> TransportReporter<T> {
+        TransportReporter {
+            transport,
+            capacity: 32,
+            max_batch: 16,
+            max_retries: 5,
+            state: Mutex::new(State {
+                queue: RingBuffer::new(),
+                failures: 0,
+                retry_at: 0.0,
+            }),
+        }
+    }
+
+    /// Keep up to `capacity` panics queued, dropping the oldest ones past
+    /// that.
+    pub fn with_capacity(mut self, capacity: usize) -> TransportReporter<T> {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Send up to `max_batch` panics in each payload, or fewer if the
+    /// transport's [`max_batch`](trait.Transport.html#method.max_batch) is
+    /// lower.
+    pub fn with_max_batch(mut self, max_batch: usize) -> TransportReporter<T> {
+        self.max_batch = max_batch;
+        self
+    }
+
+    /// Retry each batch up to `max_retries` times before dropping it.
+    pub fn with_max_retries(mut self, max_retries: u32) -> TransportReporter<T> {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// The transport panics are sent with.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Try to send the queued panics now, unless a retry is due later.
+    pub fn flush(&self) {
+        self.flush_queue(&mut lock(&self.state));
+    }
+
+    fn flush_queue(&self, state: &mut State) {
+        let max_batch = self.max_batch.min(self.transport.max_batch()).max(1);
+        while !state.queue.is_empty() {
+            // Without a clock, every attempt is a retry.
+            let now = imp::now();
+            if now.is_some_and(|now| now < state.retry_at) {
+                return;
+            }
+
+            let batch = state.queue.oldest(max_batch);
+            let delivered = match self.transport.serialize(&batch) {
+                Some(payload) => self.transport.deliver(&payload),
+                None => true,
+            };
+            if delivered || state.failures >= self.max_retries {
+                state.queue.pop_oldest(batch.len());
+                state.failures = 0;
+                state.retry_at = 0.0;
+            } else {
+                let backoff = INITIAL_BACKOFF * 2f64.powi(state.failures as i32);
+                state.failures += 1;
+                state.retry_at = now.unwrap_or(0.0) + backoff.min(MAX_BACKOFF);
+                return;
+            }
+        }
+    }
+}
+
+impl<T: Transport> Reporter for TransportReporter<T> {
+    fn report(&self, report: &PanicReport) {
+        let mut state = lock(&self.state);
+        state.queue.push(report.clone(), self.capacity);
+        self.flush_queue(&mut state);
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
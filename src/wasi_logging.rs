@@ -0,0 +1,13 @@
+//! Logging through the `wasi:logging` interface on WASI 0.2 components.
+
+wit_bindgen::generate!({
+    path: "wit",
+    world: "panic-hook",
+});
+
+use self::wasi::logging::logging::{log, Level};
+
+/// Log `msg` at `error` level, for the host to capture however it likes.
+pub(crate) fn error(msg: &str) {
+    log(Level::Error, "panic", msg);
+}
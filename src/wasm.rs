@@ -0,0 +1,1279 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[cfg(all(feature = "console-log", not(feature = "disabled")))]
+use console_log;
+#[cfg(all(feature = "console-log", not(feature = "disabled")))]
+use log::Level;
+use wasm_bindgen::prelude::*;
+
+#[cfg(feature = "drain")]
+use super::BufferReporter;
+use super::{console, report};
+#[cfg(feature = "indexeddb")]
+use super::{Breadcrumb, BuildInfo};
+use super::{
+    ConsoleLevel, ConsoleReporter, Environment, NodeExit, PanicReport, Runtime, Settings, Styles,
+};
+
+#[cfg(feature = "indexeddb")]
+#[wasm_bindgen(module = "/js/indexeddb.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = append)]
+    fn indexeddb_append(record: &JsValue, capacity: f64);
+
+    #[wasm_bindgen(js_name = getAll)]
+    fn indexeddb_get_all() -> JsValue;
+
+    #[wasm_bindgen(js_name = getAllThen)]
+    fn indexeddb_get_all_then(callback: &JsValue);
+
+    #[wasm_bindgen(js_name = clear)]
+    fn indexeddb_clear() -> JsValue;
+}
+
+#[cfg(not(feature = "gloo-console"))]
+#[wasm_bindgen]
+extern "C" {
+    // These `catch` exceptions, since there are places without a `console`
+    // (or with a broken one), and we would rather buffer panics there.
+
+    #[wasm_bindgen(catch, js_namespace = console, js_name = error)]
+    fn error_value(msg: &JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, variadic, js_namespace = console, js_name = error)]
+    fn error_values(values: Box<[JsValue]>) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, variadic, js_namespace = console, js_name = warn)]
+    fn warn_values(values: Box<[JsValue]>) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, variadic, js_namespace = console, js_name = log)]
+    fn log_values(values: Box<[JsValue]>) -> Result<(), JsValue>;
+
+    // Messages on their own are passed as `&str`, which the glue copies
+    // straight out of wasm memory, instead of boxing up a `JsValue` first.
+
+    #[wasm_bindgen(catch, js_namespace = console, js_name = warn)]
+    fn warn_str(msg: &str) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = console, js_name = log)]
+    fn log_str(msg: &str) -> Result<(), JsValue>;
+}
+
+// `gloo_console` doesn't catch exceptions, so with it a missing or broken
+// `console` throws instead of leaving the panic undelivered.
+
+#[cfg(feature = "gloo-console")]
+fn error_value(msg: &JsValue) -> Result<(), JsValue> {
+    ::gloo_console::error!(msg);
+    Ok(())
+}
+
+#[cfg(feature = "gloo-console")]
+fn error_values(values: Box<[JsValue]>) -> Result<(), JsValue> {
+    ::gloo_console::externs::error(values);
+    Ok(())
+}
+
+#[cfg(feature = "gloo-console")]
+fn warn_values(values: Box<[JsValue]>) -> Result<(), JsValue> {
+    ::gloo_console::externs::warn(values);
+    Ok(())
+}
+
+#[cfg(feature = "gloo-console")]
+fn log_values(values: Box<[JsValue]>) -> Result<(), JsValue> {
+    ::gloo_console::externs::log(values);
+    Ok(())
+}
+
+/// Log `msg` with the `console` method for `level`.
+#[cfg(not(feature = "gloo-console"))]
+fn console_str(level: ConsoleLevel, msg: &str) -> bool {
+    match level {
+        ConsoleLevel::Error => error_str(msg),
+        ConsoleLevel::Warn => warn_str(msg),
+        ConsoleLevel::Log => log_str(msg),
+    }
+    .is_ok()
+}
+
+/// Log `msg` with the `console` method for `level`.
+#[cfg(feature = "gloo-console")]
+fn console_str(level: ConsoleLevel, msg: &str) -> bool {
+    console_values(level, vec![msg.into()])
+}
+
+/// Log `values` with the `console` method for `level`.
+fn console_values(level: ConsoleLevel, values: Vec<JsValue>) -> bool {
+    let values = values.into_boxed_slice();
+    match level {
+        ConsoleLevel::Error => error_values(values),
+        ConsoleLevel::Warn => warn_values(values),
+        ConsoleLevel::Log => log_values(values),
+    }
+    .is_ok()
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = console, js_name = error)]
+    fn error_str(msg: &str) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = console)]
+    fn group(label: &str) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = console)]
+    fn trace(label: &str) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = console)]
+    fn table(data: &Object) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = console, js_name = groupEnd)]
+    fn group_end() -> Result<(), JsValue>;
+
+    type Error;
+
+    #[wasm_bindgen(constructor)]
+    fn new() -> Error;
+
+    #[wasm_bindgen(constructor, js_class = "Error")]
+    fn new_with_message(message: &str) -> Error;
+
+    #[wasm_bindgen(structural, method, getter)]
+    fn stack(error: &Error) -> String;
+
+    #[wasm_bindgen(catch, js_name = reportError)]
+    fn report_error(error: &Error) -> Result<(), JsValue>;
+
+    type Object;
+
+    #[wasm_bindgen(constructor)]
+    fn new() -> Object;
+
+    #[wasm_bindgen(js_namespace = Reflect)]
+    fn set(target: &Object, key: &str, value: &JsValue) -> bool;
+
+    #[wasm_bindgen(catch, js_namespace = Reflect)]
+    fn get(target: &JsValue, key: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = Object)]
+    fn keys(object: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_namespace = JSON)]
+    fn stringify(value: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_namespace = Date, js_name = now)]
+    fn date_now() -> f64;
+
+    type Global;
+
+    #[wasm_bindgen(thread_local_v2, js_name = globalThis)]
+    static GLOBAL: Global;
+
+    #[wasm_bindgen(method, catch, js_name = dispatchEvent)]
+    fn dispatch_event(global: &Global, event: &CustomEvent) -> Result<bool, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = addEventListener)]
+    fn add_event_listener(global: &Global, type_: &str, listener: &JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(catch, js_name = String)]
+    fn to_string(value: &JsValue) -> Result<String, JsValue>;
+
+    #[wasm_bindgen(js_namespace = WebAssembly)]
+    type RuntimeError;
+
+    type Event;
+
+    #[wasm_bindgen(method, js_name = preventDefault)]
+    fn prevent_default(event: &Event);
+
+    #[derive(Clone)]
+    type Function;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(body: &str) -> Result<Function, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = call)]
+    fn call0(function: &Function, this: &JsValue) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = call)]
+    fn call1(function: &Function, this: &JsValue, arg: &JsValue) -> Result<JsValue, JsValue>;
+
+    type CustomEvent;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(type_: &str, init: &Object) -> Result<CustomEvent, JsValue>;
+
+    type UrlSearchParams;
+
+    #[wasm_bindgen(constructor, catch, js_class = URLSearchParams)]
+    fn new(init: &str) -> Result<UrlSearchParams, JsValue>;
+
+    #[wasm_bindgen(method, js_class = URLSearchParams)]
+    fn get(params: &UrlSearchParams, name: &str) -> Option<String>;
+
+    type NodeProcess;
+
+    #[wasm_bindgen(method, catch, js_name = getBuiltinModule)]
+    fn get_builtin_module(process: &NodeProcess, name: &str) -> Result<NodeFs, JsValue>;
+
+    #[wasm_bindgen(method, getter)]
+    fn stderr(process: &NodeProcess) -> NodeWritable;
+
+    #[wasm_bindgen(method, setter = exitCode)]
+    fn set_exit_code(process: &NodeProcess, code: i32);
+
+    #[wasm_bindgen(method)]
+    fn exit(process: &NodeProcess, code: i32);
+
+    type NodeFs;
+
+    #[wasm_bindgen(method, catch, js_name = writeSync)]
+    fn write_sync(fs: &NodeFs, fd: u32, data: &str) -> Result<JsValue, JsValue>;
+
+    type NodeWritable;
+
+    #[wasm_bindgen(method, catch)]
+    fn write(stream: &NodeWritable, data: &str) -> Result<JsValue, JsValue>;
+
+    type WorkerGlobalScope;
+
+    type DedicatedWorkerGlobalScope;
+
+    #[wasm_bindgen(method, catch, js_name = postMessage)]
+    fn post_message(scope: &DedicatedWorkerGlobalScope, message: &JsValue) -> Result<(), JsValue>;
+
+    type BroadcastChannel;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(name: &str) -> Result<BroadcastChannel, JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = postMessage)]
+    fn post_message(channel: &BroadcastChannel, message: &JsValue) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method)]
+    fn close(channel: &BroadcastChannel);
+
+    #[wasm_bindgen(catch, js_name = fetch)]
+    fn fetch_with_init(url: &str, init: &Object) -> Result<Promise, JsValue>;
+
+    type Promise;
+
+    #[wasm_bindgen(method, js_name = catch)]
+    fn catch(promise: &Promise, on_rejected: &JsValue) -> Promise;
+
+    type WebSocket;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(url: &str) -> Result<WebSocket, JsValue>;
+
+    #[wasm_bindgen(method, getter = readyState)]
+    fn ready_state(socket: &WebSocket) -> u16;
+
+    #[wasm_bindgen(method, catch)]
+    fn send(socket: &WebSocket, data: &JsValue) -> Result<(), JsValue>;
+
+    type Array;
+
+    #[wasm_bindgen(constructor)]
+    fn new() -> Array;
+
+    #[wasm_bindgen(method)]
+    fn push(array: &Array, value: &JsValue) -> u32;
+
+    type Navigator;
+
+    #[wasm_bindgen(method, catch, js_name = sendBeacon)]
+    fn send_beacon(navigator: &Navigator, url: &str, data: &JsValue) -> Result<bool, JsValue>;
+
+    type Performance;
+
+    #[wasm_bindgen(method, catch)]
+    fn mark(performance: &Performance, name: &str, options: &Object) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method)]
+    fn now(performance: &Performance) -> f64;
+
+    type Storage;
+
+    #[wasm_bindgen(method, catch, js_name = setItem)]
+    fn set_item(storage: &Storage, key: &str, value: &JsValue) -> Result<(), JsValue>;
+
+    type Window;
+
+    #[wasm_bindgen(method, catch, js_name = postMessage)]
+    fn post_message_to(
+        window: &Window,
+        message: &JsValue,
+        target_origin: &str,
+    ) -> Result<(), JsValue>;
+}
+
+pub(crate) fn now() -> Option<f64> {
+    Some(date_now())
+}
+
+pub(crate) fn capture_stack(_force: bool) -> Option<String> {
+    Some(Error::new().stack())
+}
+
+fn with_whitespace(msg: &str) -> String {
+    let mut msg = msg.to_string();
+
+    // Unlike Firefox's, Safari's devtools _do_ mess with logged messages'
+    // contents, so we attempt to break their heuristics for doing that by
+    // appending some whitespace.
+    // https://github.com/rustwasm/console_error_panic_hook/issues/7
+    msg.push_str("\n\n");
+    msg
+}
+
+/// The panic traps the module anyway, so there is nothing to do here.
+pub(crate) fn abort() {}
+
+/// Throw a JavaScript `Error` with `msg`, instead of letting the module trap.
+pub(crate) fn throw(msg: &str) {
+    wasm_bindgen::throw_str(msg);
+}
+
+/// Log `msg`, returning whether that worked.
+pub(crate) fn console_error(msg: &str) -> bool {
+    console_str(ConsoleLevel::Error, &with_whitespace(msg))
+}
+
+/// Log `msg` without allocating, returning whether that worked. It is copied
+/// straight from wasm memory into a JavaScript string.
+///
+/// Unlike `console_error`, this doesn't add trailing whitespace. That is
+/// there to keep Safari from mangling stacks, and these messages have none.
+pub(crate) fn log_without_allocating(msg: &str) -> bool {
+    error_str(msg).is_ok()
+}
+
+pub(crate) fn console_report(console: &ConsoleReporter, report: &PanicReport) -> bool {
+    let logged = log_report(console, report);
+    if console.table {
+        let _ = table(&table_rows(report));
+    }
+    if console.trace {
+        let _ = trace(&console::group_label(report));
+    }
+    logged
+}
+
+fn log_report(console: &ConsoleReporter, report: &PanicReport) -> bool {
+    if console.report_error {
+        // This throws where there is no `reportError`, which leaves logging
+        // the panic to `console.error` below.
+        let _ = report_error(&Error::new_with_message(report.message()));
+    }
+
+    // Deno prints objects logged after the message with its own inspector, so
+    // the fields are always worth passing there. Its terminal also handles
+    // colors, unless they have been turned off.
+    let deno = deno();
+    let object = console.object || deno.is_some();
+    #[cfg(feature = "color")]
+    let colored = deno
+        .filter(|deno| !property(deno, "noColor").is_truthy())
+        .map(|_| console::colorize(report));
+    #[cfg(not(feature = "color"))]
+    let colored: Option<String> = None;
+    let msg = colored.as_deref().unwrap_or(report.message());
+
+    if console.node_stderr {
+        if let Some(process) = node() {
+            if node_stderr(&process, msg) {
+                return true;
+            }
+        }
+    }
+
+    if console.group && group(&console::group_label(report)).is_ok() {
+        let sections = console::sections(report);
+        let mut logged = true;
+        for (i, section) in sections.iter().enumerate() {
+            // Only the stack needs protecting from Safari, and it comes last.
+            let section = if i + 1 == sections.len() {
+                Cow::Owned(with_whitespace(section))
+            } else {
+                Cow::Borrowed(section.as_str())
+            };
+            logged &= match console.styles {
+                Some(ref styles) if i == 0 => {
+                    console_styled(console.level, &section, report, styles, None)
+                }
+                _ => console_str(console.level, &section),
+            };
+        }
+        if object {
+            logged &= console_values(console.level, vec![fields(report).into()]);
+        }
+        let _ = group_end();
+        return logged;
+    }
+
+    let fields = if object { Some(fields(report)) } else { None };
+
+    if let Some(ref styles) = console.styles {
+        if !console.error_object {
+            let msg = with_whitespace(report.message());
+            return console_styled(console.level, &msg, report, styles, fields.as_ref());
+        }
+    }
+
+    let msg = with_whitespace(msg);
+    if fields.is_none() && !console.error_object {
+        // Finally, log the panic with `console.error`, or whichever method
+        // was asked for!
+        return console_str(console.level, &msg);
+    }
+    let msg = if console.error_object {
+        // Browsers render a logged `Error` with its own, source-mapped stack.
+        JsValue::from(Error::new_with_message(&msg))
+    } else {
+        JsValue::from_str(&msg)
+    };
+
+    // Or along with the fields.
+    let mut values = vec![msg];
+    values.extend(fields.map(JsValue::from));
+    console_values(console.level, values)
+}
+
+/// Log `msg`, which is `report`'s message or part of it, styled with `%c`
+/// directives, followed by `object` if given.
+fn console_styled(
+    level: ConsoleLevel,
+    msg: &str,
+    report: &PanicReport,
+    styles: &Styles,
+    object: Option<&Object>,
+) -> bool {
+    let (format, css) = console::stylize(msg, report, styles);
+    let mut values = vec![JsValue::from(format)];
+    values.extend(css.into_iter().map(JsValue::from));
+    values.extend(object.map(JsValue::from));
+    console_values(level, values)
+}
+
+pub(crate) fn detect_runtime() -> Runtime {
+    if deno().is_some() {
+        Runtime::Deno
+    } else if node().is_some() {
+        Runtime::Node
+    } else if GLOBAL.with(|global| global.is_instance_of::<WorkerGlobalScope>()) {
+        Runtime::Worker
+    } else if global("window").is_some() {
+        Runtime::Browser
+    } else {
+        Runtime::Unknown
+    }
+}
+
+/// Route `log` records at `level` and above to the console, unless another
+/// logger was set already.
+#[cfg(all(feature = "console-log", not(feature = "disabled")))]
+pub(crate) fn init_logger(level: Level) {
+    let _ = console_log::init_with_level(level);
+}
+
+/// Pause on a `debugger` statement, if devtools are open.
+///
+/// There is no binding for a statement, so this evaluates one, which a
+/// Content Security Policy without `'unsafe-eval'` forbids.
+pub(crate) fn debugger() {
+    if let Ok(debugger) = Function::new("debugger") {
+        let _ = debugger.call0(&JsValue::UNDEFINED);
+    }
+}
+
+pub(crate) fn node_exit(node_exit: NodeExit) {
+    if let Some(process) = node() {
+        match node_exit {
+            NodeExit::Ignore => {}
+            NodeExit::SetExitCode(code) => process.set_exit_code(code),
+            NodeExit::Exit(code) => process.exit(code),
+        }
+    }
+}
+
+/// Log the whole report as a single object.
+pub(crate) fn console_record(report: &PanicReport) -> bool {
+    error_value(&record(report).into()).is_ok()
+}
+
+/// `target[key]`, or `undefined` if `target` isn't an object.
+fn property(target: &JsValue, key: &str) -> JsValue {
+    get(target, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+}
+
+/// The global `name`, if it is defined.
+fn global(name: &str) -> Option<JsValue> {
+    let value = GLOBAL.with(|global| property(global, name));
+    if value.is_undefined() || value.is_null() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// The `Deno` namespace, if we are running under Deno.
+fn deno() -> Option<JsValue> {
+    global("Deno")
+}
+
+/// Node's `process`, if we are running under Node.
+fn node() -> Option<NodeProcess> {
+    global("process")
+        .filter(|process| property(&property(process, "versions"), "node").is_string())
+        .map(JsCast::unchecked_into)
+}
+
+/// Write `msg` to Node's `stderr` synchronously, returning whether that
+/// worked.
+fn node_stderr(process: &NodeProcess, msg: &str) -> bool {
+    let line = format!("{}\n", msg);
+    // `process.stderr.write` is only synchronous for files and terminals, not
+    // for pipes, so go through `fs` if we can get at it.
+    if let Ok(fs) = process.get_builtin_module("fs") {
+        if fs.write_sync(2, &line).is_ok() {
+            return true;
+        }
+    }
+    process.stderr().write(&line).is_ok()
+}
+
+pub(crate) fn post_to_main_thread(report: &PanicReport) {
+    GLOBAL.with(|global| {
+        // Outside a dedicated worker, there is no main thread to post to.
+        if let Some(scope) = global.dyn_ref::<DedicatedWorkerGlobalScope>() {
+            let _ = scope.post_message(&message(report));
+        }
+    });
+}
+
+pub(crate) fn post_to_parent(report: &PanicReport, target_origin: &str) {
+    let (window, parent) = match (global("window"), global("parent")) {
+        (Some(window), Some(parent)) => (window, parent),
+        _ => return,
+    };
+    // A top-level window is its own parent.
+    if window != parent {
+        let parent: Window = parent.unchecked_into();
+        let _ = parent.post_message_to(&message(report), target_origin);
+    }
+}
+
+pub(crate) fn broadcast(report: &PanicReport, channel: &str) {
+    // Not every runtime has `BroadcastChannel`.
+    if let Ok(channel) = BroadcastChannel::new(channel) {
+        // Messages already posted are still delivered once it is closed.
+        let _ = channel.post_message(&record(report).into());
+        channel.close();
+    }
+}
+
+thread_local! {
+    /// A function that does nothing, for ignoring rejected promises.
+    static IGNORE: Closure<dyn FnMut(JsValue)> = Closure::new(|_| {});
+}
+
+/// The report as JSON: an object like those posted by `WorkerReporter`s.
+pub(crate) fn report_to_json(report: &PanicReport, with_stack: bool) -> Option<String> {
+    stringify(&json_record(report, with_stack))
+        .ok()?
+        .as_string()
+}
+
+/// The reports as JSON: an array of objects like those posted by
+/// `WorkerReporter`s, even if there is only one of them.
+pub(crate) fn to_json(reports: &[PanicReport], with_stack: bool) -> Option<String> {
+    let array = Array::new();
+    for report in reports {
+        array.push(&json_record(report, with_stack));
+    }
+    stringify(&array).ok()?.as_string()
+}
+
+/// The record of `report`, without its stack unless `with_stack`.
+fn json_record(report: &PanicReport, with_stack: bool) -> JsValue {
+    let record = record(report);
+    if !with_stack {
+        set(&record, "stack", &JsValue::UNDEFINED);
+    }
+    record.into()
+}
+
+/// `POST` `body` to `url` with `keepalive`, returning whether the request was
+/// made. Whether it succeeds is never known.
+pub(crate) fn post(
+    url: &str,
+    content_type: &str,
+    headers: &[(String, String)],
+    body: &str,
+) -> bool {
+    let header_object = Object::new();
+    set(
+        &header_object,
+        "Content-Type",
+        &JsValue::from_str(content_type),
+    );
+    for (name, value) in headers {
+        set(&header_object, name, &JsValue::from_str(value));
+    }
+    let init = Object::new();
+    set(&init, "method", &JsValue::from_str("POST"));
+    set(&init, "keepalive", &JsValue::TRUE);
+    set(&init, "headers", &header_object.into());
+    set(&init, "body", &JsValue::from_str(body));
+
+    // Not every runtime has `fetch`, and a failed request would otherwise be
+    // reported as an unhandled rejection.
+    match fetch_with_init(url, &init) {
+        Ok(promise) => {
+            IGNORE.with(|ignore| promise.catch(ignore.as_ref()));
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+thread_local! {
+    /// The sockets opened by `WebSocketReporter`s on this thread, by URL.
+    static SOCKETS: RefCell<Vec<(String, WebSocket)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// `WebSocket.OPEN`.
+const OPEN: u16 = 1;
+
+/// `WebSocket.CLOSING`.
+const CLOSING: u16 = 2;
+
+pub(crate) fn open_websocket(url: &str) {
+    // Not every runtime has `WebSocket`, and it throws for invalid URLs.
+    if let Ok(socket) = WebSocket::new(url) {
+        SOCKETS.with(|sockets| {
+            let mut sockets = sockets.borrow_mut();
+            sockets.retain(|(opened, _)| opened != url);
+            sockets.push((url.to_string(), socket));
+        });
+    }
+}
+
+/// Send `payload` over this thread's socket to `url`, returning whether it
+/// was open to send it.
+pub(crate) fn send_websocket(url: &str, payload: &str) -> bool {
+    let state = SOCKETS.with(|sockets| {
+        let sockets = sockets.borrow();
+        let (_, socket) = sockets.iter().find(|(opened, _)| opened == url)?;
+        let state = socket.ready_state();
+        let sent = state == OPEN && socket.send(&JsValue::from_str(payload)).is_ok();
+        Some((state, sent))
+    });
+    match state {
+        Some((_, true)) => true,
+        // Still connecting, so there is nothing to do but wait.
+        Some((state, false)) if state < CLOSING => false,
+        // Reconnect for the next panic, if this socket's server went away.
+        _ => {
+            open_websocket(url);
+            false
+        }
+    }
+}
+
+/// Queue `payload` for sending to `url` with `sendBeacon`, returning whether
+/// it was queued.
+pub(crate) fn send_beacon(url: &str, payload: &str) -> bool {
+    let navigator = match global("navigator") {
+        Some(navigator) => navigator.unchecked_into::<Navigator>(),
+        None => return false,
+    };
+    // This throws for invalid URLs, and returns `false` once too much is
+    // queued.
+    navigator
+        .send_beacon(url, &JsValue::from_str(payload))
+        .unwrap_or(false)
+}
+
+/// What `navigator` tells about where we are running, if there is one.
+pub(crate) fn environment() -> Option<Environment> {
+    let navigator = global("navigator")?;
+    // `userAgentData` is the User-Agent Client Hints API, which not every
+    // browser has, and which leaves `platform` empty where it can't tell.
+    let platform = property(&property(&navigator, "userAgentData"), "platform")
+        .as_string()
+        .filter(|platform| !platform.is_empty())
+        .or_else(|| property(&navigator, "platform").as_string());
+    Some(Environment {
+        user_agent: property(&navigator, "userAgent").as_string(),
+        platform,
+        hardware_concurrency: property(&navigator, "hardwareConcurrency")
+            .as_f64()
+            .map(|n| n as u32),
+    })
+}
+
+/// `performance.now()`, if there is a `performance`.
+pub(crate) fn uptime() -> Option<f64> {
+    global("performance").map(|performance| performance.unchecked_into::<Performance>().now())
+}
+
+pub(crate) fn performance_mark(report: &PanicReport, name: &str) {
+    let options = Object::new();
+    set(&options, "detail", &record(report).into());
+
+    // Older runtimes have no `performance`, or a `mark` that throws when the
+    // detail can't be cloned.
+    if let Some(performance) = global("performance") {
+        let _ = performance
+            .unchecked_into::<Performance>()
+            .mark(name, &options);
+    }
+}
+
+pub(crate) fn store_in_session(report: &PanicReport, key: &str) {
+    // Merely getting at `sessionStorage` throws in some sandboxed iframes, and
+    // `setItem` throws once it is full.
+    if let Some(storage) = global("sessionStorage") {
+        if let Ok(json) = stringify(&record(report).into()) {
+            let _ = storage.unchecked_into::<Storage>().set_item(key, &json);
+        }
+    }
+}
+
+/// The message posted to other windows and threads: the report, tagged so it
+/// can be told apart from the page's own messages.
+fn message(report: &PanicReport) -> JsValue {
+    let message = Object::new();
+    set(
+        &message,
+        "type",
+        &JsValue::from_str("console_error_panic_hook"),
+    );
+    set(&message, "report", &record(report).into());
+    message.into()
+}
+
+pub(crate) fn dispatch_panic_event(report: &PanicReport) {
+    let init = Object::new();
+    set(&init, "detail", &record(report).into());
+
+    // Not every runtime has `CustomEvent`, or a global object that is an
+    // `EventTarget`.
+    if let Ok(event) = CustomEvent::new("rust-panic", &init) {
+        GLOBAL.with(|global| {
+            let _ = global.dispatch_event(&event);
+        });
+    }
+}
+
+/// Apply the `CONSOLE_ERROR_PANIC_HOOK` global: either an object like
+/// `{ verbosity: "short", prefix: "[app]", remote: false }`, or a list of
+/// settings like the `panic_hook` query parameter's.
+pub(crate) fn global_settings(settings: &mut Settings) {
+    let global = match global("CONSOLE_ERROR_PANIC_HOOK") {
+        Some(global) => global,
+        None => return,
+    };
+    if let Some(list) = global.as_string() {
+        settings.apply_list(&list);
+        return;
+    }
+    if let Some(verbosity) = property(&global, "verbosity").as_string() {
+        settings.apply_list(&verbosity);
+    }
+    if let Some(prefix) = property(&global, "prefix").as_string() {
+        settings.prefix = Some(prefix);
+    }
+    if let Some(remote) = property(&global, "remote").as_bool() {
+        settings.remote = Some(remote);
+    }
+}
+
+/// The page URL's `panic_hook` query parameter, as in `?panic_hook=verbose`.
+pub(crate) fn settings_list() -> Option<String> {
+    let search = property(&global("location")?, "search").as_string()?;
+    UrlSearchParams::new(&search).ok()?.get("panic_hook")
+}
+
+/// Whether the `RUST_BACKTRACE` global is set, if it is, like the variable it
+/// is named after: anything but `"0"` (or a falsy value) turns it on.
+pub(crate) fn backtrace_switch() -> Option<bool> {
+    let switch = global("RUST_BACKTRACE")?;
+    Some(match switch.as_string() {
+        Some(switch) => !switch.is_empty() && switch != "0",
+        None => switch.is_truthy(),
+    })
+}
+
+/// Call `handler` with the reason and stack of every unhandled promise
+/// rejection that comes from wasm, if the global takes an `unhandledrejection`
+/// listener. Node's global doesn't, and a `process` listener there would keep
+/// unhandled rejections from failing the process.
+pub(crate) fn on_unhandled_rejection<F: Fn(&str, Option<String>) + 'static>(handler: F) {
+    let listener = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+        let reason = property(&event, "reason");
+        if !is_wasm_error(&reason) {
+            return;
+        }
+        let description = to_string(&reason).unwrap_or_else(|_| "unknown error".to_string());
+        handler(&description, property(&reason, "stack").as_string());
+    });
+    add_global_listener("unhandledrejection", listener);
+}
+
+/// Call `handler` with every uncaught `RuntimeError: unreachable`, which is
+/// what a panic traps the module with, if the global takes an `error`
+/// listener. When `handler` returns `true`, the browser doesn't log the error
+/// itself.
+pub(crate) fn on_trap<F: Fn(&str) -> bool + 'static>(handler: F) {
+    let listener = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+        let error = property(&event, "error");
+        if !error.is_instance_of::<RuntimeError>() {
+            return;
+        }
+        // Firefox says "unreachable executed", and Safari "Unreachable code
+        // should not be executed".
+        let description = to_string(&error).unwrap_or_else(|_| "RuntimeError".to_string());
+        if description.to_lowercase().contains("unreachable") && handler(&description) {
+            event.unchecked_ref::<Event>().prevent_default();
+        }
+    });
+    add_global_listener("error", listener);
+}
+
+/// Add `listener` for `type_` events on the global, for as long as the page
+/// lasts, if the global is an `EventTarget`.
+fn add_global_listener(type_: &str, listener: Closure<dyn FnMut(JsValue)>) {
+    let added = GLOBAL.with(|global| global.add_event_listener(type_, listener.as_ref()).is_ok());
+    if added {
+        listener.forget();
+    }
+}
+
+/// Whether `error` is a trap, or was thrown with wasm frames on its stack.
+/// Chrome, Node and Deno name them `wasm-function[...]`, and Firefox and
+/// Safari give their `wasm://` URLs.
+fn is_wasm_error(error: &JsValue) -> bool {
+    if error.is_instance_of::<RuntimeError>() {
+        return true;
+    }
+    property(error, "stack")
+        .as_string()
+        .is_some_and(|stack| stack.contains("wasm-function[") || stack.contains("wasm://"))
+}
+
+/// Call `f` through JavaScript, so that a panic in it traps out to the
+/// `catch` of the call rather than taking the whole module down, even while
+/// reporting a panic.
+pub(crate) fn call_isolated<T: 'static, F: FnOnce() -> T + 'static>(f: F) -> Option<T> {
+    let result = Rc::new(RefCell::new(None));
+    let slot = result.clone();
+    let closure = Closure::once(move || {
+        let value = f();
+        *slot.borrow_mut() = Some(value);
+    });
+    let called = closure
+        .as_ref()
+        .unchecked_ref::<Function>()
+        .call0(&JsValue::UNDEFINED);
+    let value = result.borrow_mut().take();
+    called.ok().and(value)
+}
+
+/// Whether there is an `__onRustPanic` callback, or a panic listener, to
+/// call with each panic.
+pub(crate) fn has_js_callbacks() -> bool {
+    #[cfg(feature = "listeners")]
+    {
+        if LISTENERS.with(|listeners| !listeners.borrow().is_empty()) {
+            return true;
+        }
+    }
+    global("__onRustPanic").is_some_and(|callback| callback.is_function())
+}
+
+/// Call the global `__onRustPanic` with the report, if there is one, and then
+/// the panic listeners.
+pub(crate) fn call_js_callbacks(report: &PanicReport) {
+    let record = JsValue::from(record(report));
+    if let Some(callback) = global("__onRustPanic").filter(JsValue::is_function) {
+        let callback: Function = callback.unchecked_into();
+        let _ = callback.call1(&JsValue::UNDEFINED, &record);
+    }
+
+    #[cfg(feature = "listeners")]
+    {
+        // Listeners may add or remove listeners, so call a copy of the list.
+        let listeners = LISTENERS.with(|listeners| listeners.borrow().clone());
+        for listener in listeners {
+            // Each listener's exceptions are its own, and don't keep the others
+            // from being called.
+            let _ = listener.call1(&JsValue::UNDEFINED, &record);
+        }
+    }
+}
+
+#[cfg(feature = "listeners")]
+thread_local! {
+    static LISTENERS: RefCell<Vec<Function>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Have the hook call `listener` with each panic on this thread, as a plain
+/// object with the same fields as those posted by `WorkerReporter`s. Adding
+/// the same listener twice, or something other than a function, does nothing.
+#[cfg(feature = "listeners")]
+#[wasm_bindgen(js_name = addPanicListener)]
+pub fn add_panic_listener(listener: JsValue) {
+    if !listener.is_function() {
+        return;
+    }
+    let listener: Function = listener.unchecked_into();
+    LISTENERS.with(|listeners| {
+        let mut listeners = listeners.borrow_mut();
+        if !listeners.iter().any(|added| **added == *listener) {
+            listeners.push(listener);
+        }
+    });
+}
+
+/// Stop calling a listener added with `addPanicListener`.
+#[cfg(feature = "listeners")]
+#[wasm_bindgen(js_name = removePanicListener)]
+pub fn remove_panic_listener(listener: JsValue) {
+    LISTENERS.with(|listeners| {
+        listeners.borrow_mut().retain(|added| **added != listener);
+    });
+}
+
+#[cfg(feature = "indexeddb")]
+pub(crate) fn append_to_indexeddb(report: &PanicReport, capacity: usize) {
+    indexeddb_append(&record(report).into(), capacity as f64);
+}
+
+#[cfg(feature = "indexeddb")]
+pub(crate) fn stored_in_indexeddb(f: Box<dyn FnOnce(Vec<PanicReport>)>) {
+    let callback = Closure::once_into_js(move |records: JsValue| {
+        let len = property(&records, "length").as_f64().unwrap_or(0.0) as u32;
+        let reports = (0..len)
+            .map(|i| get(&records, &JsValue::from(i)).unwrap_or(JsValue::UNDEFINED))
+            .map(|record| from_record(&record))
+            .collect();
+        f(reports);
+    });
+    indexeddb_get_all_then(&callback);
+}
+
+#[cfg(feature = "indexeddb")]
+pub(crate) fn clear_indexeddb() {
+    indexeddb_clear();
+}
+
+/// Resolve to the panics kept by `IndexedDbReporter`s, oldest first, as plain
+/// objects with the same fields as those posted by `WorkerReporter`s.
+#[cfg(feature = "indexeddb")]
+#[wasm_bindgen(js_name = storedPanicReports)]
+pub fn stored_panic_reports() -> JsValue {
+    indexeddb_get_all()
+}
+
+/// Remove the panics kept by `IndexedDbReporter`s, resolving once they are
+/// gone.
+#[cfg(feature = "indexeddb")]
+#[wasm_bindgen(js_name = clearPanicReports)]
+pub fn clear_panic_reports() -> JsValue {
+    indexeddb_clear()
+}
+
+/// Take the panics kept by `BufferReporter`s, oldest first, as plain objects
+/// with the same fields as those posted by `WorkerReporter`s.
+#[cfg(feature = "drain")]
+#[wasm_bindgen(js_name = drainPanicReports)]
+pub fn drain_panic_reports() -> Vec<JsValue> {
+    BufferReporter::drain()
+        .iter()
+        .map(|report| record(report).into())
+        .collect()
+}
+
+/// How many panics the hook has seen, from `panic_count`.
+#[cfg(feature = "panic-count")]
+#[wasm_bindgen(js_name = panicCount)]
+pub fn panic_count() -> u32 {
+    super::panic_count() as u32
+}
+
+/// Set the panic hook as soon as the module is instantiated.
+#[cfg(feature = "auto-install")]
+#[wasm_bindgen(start)]
+fn auto_install() {
+    super::set_once();
+}
+
+/// A JavaScript `Error` with the panic's message, which also has the report in
+/// its `report` property, as the same object `__onRustPanic` gets. This is
+/// what an exported function returning `Result<_, PanicReport>` throws or
+/// rejects with when it panics.
+impl From<PanicReport> for JsValue {
+    fn from(report: PanicReport) -> JsValue {
+        let error = Error::new_with_message(report.message());
+        set(error.unchecked_ref(), "report", &record(&report).into());
+        error.into()
+    }
+}
+
+/// Everything in the report, for sending it elsewhere as a plain object.
+fn record(report: &PanicReport) -> Object {
+    let object = fields(report);
+    let optional_str = |s: Option<&str>| s.map_or(JsValue::UNDEFINED, JsValue::from_str);
+    set(&object, "message", &JsValue::from_str(report.message()));
+    set(&object, "stack", &optional_str(report.stack()));
+    let optional_f64 = |n: Option<f64>| n.map_or(JsValue::UNDEFINED, JsValue::from_f64);
+    set(&object, "timestamp", &optional_f64(report.timestamp()));
+    set(&object, "uptime", &optional_f64(report.uptime()));
+    set(
+        &object,
+        "memory",
+        &optional_f64(report.memory().map(|bytes| bytes as f64)),
+    );
+    if let Some(environment) = report.environment() {
+        let snapshot = Object::new();
+        let optional_u32 =
+            |n: Option<u32>| n.map_or(JsValue::UNDEFINED, |n| JsValue::from_f64(n.into()));
+        set(
+            &snapshot,
+            "userAgent",
+            &optional_str(environment.user_agent()),
+        );
+        set(&snapshot, "platform", &optional_str(environment.platform()));
+        set(
+            &snapshot,
+            "hardwareConcurrency",
+            &optional_u32(environment.hardware_concurrency()),
+        );
+        set(&object, "environment", &snapshot.into());
+    }
+    set(&object, "moduleName", &optional_str(report.module_name()));
+    set(&object, "sourceLink", &optional_str(report.source_link()));
+    if let Some(build) = report.build() {
+        let info = Object::new();
+        set(&info, "version", &JsValue::from_str(build.version()));
+        set(&info, "gitHash", &optional_str(build.git_hash()));
+        set(&info, "buildTime", &optional_str(build.build_time()));
+        set(&object, "build", &info.into());
+    }
+    let breadcrumbs = Array::new();
+    for breadcrumb in report.breadcrumbs() {
+        let crumb = Object::new();
+        set(
+            &crumb,
+            "category",
+            &JsValue::from_str(breadcrumb.category()),
+        );
+        set(&crumb, "message", &JsValue::from_str(breadcrumb.message()));
+        set(&crumb, "timestamp", &optional_f64(breadcrumb.timestamp()));
+        breadcrumbs.push(&crumb.into());
+    }
+    set(&object, "breadcrumbs", &breadcrumbs.into());
+    let context = Array::new();
+    for line in report.context() {
+        context.push(&JsValue::from_str(line));
+    }
+    set(&object, "context", &context.into());
+    let state = Object::new();
+    for (key, value) in report.state() {
+        set(&state, key, &JsValue::from_str(value));
+    }
+    set(&object, "state", &state.into());
+    object
+}
+
+/// Turn an object made by `record` back into a report.
+#[cfg(feature = "indexeddb")]
+fn from_record(record: &JsValue) -> PanicReport {
+    let optional_str = |key| property(record, key).as_string();
+    let optional_u32 = |key| property(record, key).as_f64().map(|n| n as u32);
+    PanicReport {
+        message: optional_str("message").unwrap_or_default(),
+        payload: optional_str("payload"),
+        file: optional_str("file"),
+        line: optional_u32("line"),
+        column: optional_u32("column"),
+        thread: optional_str("thread").unwrap_or_default(),
+        timestamp: property(record, "timestamp").as_f64(),
+        uptime: property(record, "uptime").as_f64(),
+        memory: property(record, "memory").as_f64().map(|n| n as u64),
+        environment: Some(property(record, "environment"))
+            .filter(JsValue::is_object)
+            .map(|environment| Environment {
+                user_agent: property(&environment, "userAgent").as_string(),
+                platform: property(&environment, "platform").as_string(),
+                hardware_concurrency: property(&environment, "hardwareConcurrency")
+                    .as_f64()
+                    .map(|n| n as u32),
+            }),
+        build: Some(property(record, "build"))
+            .filter(JsValue::is_object)
+            .map(|build| BuildInfo {
+                version: property(&build, "version").as_string().unwrap_or_default(),
+                git_hash: property(&build, "gitHash").as_string(),
+                build_time: property(&build, "buildTime").as_string(),
+            }),
+        breadcrumbs: elements(&property(record, "breadcrumbs"))
+            .iter()
+            .map(|crumb| Breadcrumb {
+                category: property(crumb, "category").as_string().unwrap_or_default(),
+                message: property(crumb, "message").as_string().unwrap_or_default(),
+                timestamp: property(crumb, "timestamp").as_f64(),
+            })
+            .collect(),
+        context: elements(&property(record, "context"))
+            .iter()
+            .filter_map(JsValue::as_string)
+            .collect(),
+        state: {
+            let state = property(record, "state");
+            let keys = Object::keys(&state).unwrap_or(JsValue::UNDEFINED);
+            elements(&keys)
+                .iter()
+                .filter_map(JsValue::as_string)
+                .map(|key| {
+                    let value = property(&state, &key).as_string().unwrap_or_default();
+                    (key, value)
+                })
+                .collect()
+        },
+        module_name: optional_str("moduleName"),
+        stack: optional_str("stack"),
+        source_link: optional_str("sourceLink"),
+    }
+}
+
+/// The elements of `array`, or none if it isn't one.
+#[cfg(feature = "indexeddb")]
+fn elements(array: &JsValue) -> Vec<JsValue> {
+    let length = property(array, "length").as_f64().unwrap_or(0.0) as u32;
+    (0..length)
+        .map(|i| get(array, &JsValue::from_f64(i.into())).unwrap_or(JsValue::UNDEFINED))
+        .collect()
+}
+
+/// The panic's fields, followed by a row for each line of its context, each
+/// of its state's pairs, each breadcrumb, and its memory size, for
+/// `console.table`.
+fn table_rows(report: &PanicReport) -> Object {
+    let rows = fields(report);
+    for (i, line) in report.context().iter().enumerate() {
+        set(
+            &rows,
+            &format!("context {}", i + 1),
+            &JsValue::from_str(line),
+        );
+    }
+    for (key, value) in report.state() {
+        set(&rows, key, &JsValue::from_str(value));
+    }
+    // Breadcrumbs read best as the section of the message has them, with
+    // their time and category.
+    if let Some(section) = report::breadcrumbs_section(report) {
+        for (i, line) in section.lines().skip(1).enumerate() {
+            let row = format!("breadcrumb {}", i + 1);
+            set(&rows, &row, &JsValue::from_str(line.trim_start()));
+        }
+    }
+    if let Some(section) = report::memory_section(report) {
+        let memory = section.trim_start_matches("Memory: ");
+        set(&rows, "memory", &JsValue::from_str(memory));
+    }
+    rows
+}
+
+/// An object holding the panic's individual fields, for devtools to expand.
+fn fields(report: &PanicReport) -> Object {
+    let object = Object::new();
+    let optional_str = |s: Option<&str>| s.map_or(JsValue::UNDEFINED, JsValue::from_str);
+    let optional_u32 =
+        |n: Option<u32>| n.map_or(JsValue::UNDEFINED, |n| JsValue::from_f64(n.into()));
+    set(&object, "payload", &optional_str(report.payload()));
+    set(&object, "file", &optional_str(report.file()));
+    set(&object, "line", &optional_u32(report.line()));
+    set(&object, "column", &optional_u32(report.column()));
+    set(&object, "thread", &JsValue::from_str(report.thread()));
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "indexeddb")]
+    use super::{from_record, record};
+    use super::{property, report_to_json, table_rows, to_json};
+    use wasm_bindgen_test::wasm_bindgen_test;
+    use {Breadcrumb, BuildInfo, Environment, PanicReport};
+
+    fn report() -> PanicReport {
+        PanicReport {
+            message: "panicked at src/lib.rs:3:5:\nboom".to_string(),
+            payload: Some("boom".to_string()),
+            file: Some("src/lib.rs".to_string()),
+            line: Some(3),
+            column: Some(5),
+            thread: "main".to_string(),
+            timestamp: Some(1_700_000_000_000.0),
+            uptime: Some(12.5),
+            memory: Some(1 << 20),
+            environment: Some(Environment {
+                user_agent: Some("Mozilla/5.0".to_string()),
+                platform: Some("Linux".to_string()),
+                hardware_concurrency: Some(8),
+            }),
+            build: Some(BuildInfo::new("1.0.0").with_git_hash("abc123")),
+            breadcrumbs: vec![Breadcrumb {
+                category: "ui".to_string(),
+                message: "clicked save".to_string(),
+                timestamp: None,
+            }],
+            context: vec!["loading save-1.dat".to_string()],
+            state: vec![
+                ("route".to_string(), "/settings".to_string()),
+                ("user".to_string(), "7".to_string()),
+            ],
+            module_name: Some("editor".to_string()),
+            stack: Some("Error\n    at main".to_string()),
+            source_link: Some("https://example.com/src/lib.rs#L3".to_string()),
+        }
+    }
+
+    #[cfg(feature = "indexeddb")]
+    #[wasm_bindgen_test]
+    fn reads_back_records() {
+        let report = report();
+        let read_back = from_record(&record(&report).into());
+        assert_eq!(format!("{:?}", read_back), format!("{:?}", report));
+    }
+
+    #[wasm_bindgen_test]
+    fn tabulates_everything_attached() {
+        let rows = table_rows(&report());
+        let row = |key| property(&rows, key).as_string();
+        assert_eq!(row("payload").as_deref(), Some("boom"));
+        assert_eq!(row("context 1").as_deref(), Some("loading save-1.dat"));
+        assert_eq!(row("route").as_deref(), Some("/settings"));
+        assert_eq!(row("user").as_deref(), Some("7"));
+        assert_eq!(row("breadcrumb 1").as_deref(), Some("[ui] clicked save"));
+        assert_eq!(row("memory").as_deref(), Some("16 pages (1.0 MiB)"));
+    }
+    #[wasm_bindgen_test]
+    fn serializes_batches_as_arrays() {
+        let report = report();
+        let one = to_json(std::slice::from_ref(&report), true).unwrap();
+        let two = to_json(&[report.clone(), report.clone()], false).unwrap();
+        let single = report_to_json(&report, true).unwrap();
+        assert!(one.starts_with("[{") && one.ends_with("}]"));
+        assert!(two.starts_with("[{") && !two.contains("\"stack\""));
+        assert_eq!(one, format!("[{}]", single));
+    }
+}
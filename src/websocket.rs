@@ -0,0 +1,61 @@
+use super::{imp, transport, PanicReport, Reporter, Transport};
+
+/// A reporter that streams panics over a WebSocket to a log server, for
+/// watching devices that are hard to attach devtools to.
+///
+/// The socket is opened when the reporter is created, on the thread that
+/// creates it, and each panic on that thread is sent as a JSON text message
+/// with the `message`, `payload`, `file`, `line`, `column`, `thread`, `stack`,
+/// `timestamp`, `uptime`, `memory`, `environment`, `build`, and `moduleName`
+/// of the [`PanicReport`](struct.PanicReport.html). Panics are dropped while
+/// the socket isn't open; once it has closed, the next panic reopens it for
+/// those after. Where there is no `WebSocket`, and on non-wasm targets, this
+/// does nothing.
+///
+/// Used as a [`Transport`](trait.Transport.html), panics are sent as an array
+/// of such objects, even when there is only one in a batch, and those that
+/// come in while the socket isn't open are kept to be retried rather than
+/// dropped.
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, WebSocketReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(WebSocketReporter::new("wss://logs.example.com")))
+///     .install();
+/// ```
+#[derive(Clone, Debug)]
+pub struct WebSocketReporter {
+    url: String,
+}
+
+impl WebSocketReporter {
+    /// Create a reporter that sends panics to the WebSocket server at `url`,
+    /// and start connecting to it.
+    pub fn new<S: Into<String>>(url: S) -> WebSocketReporter {
+        let url = url.into();
+        imp::open_websocket(&url);
+        WebSocketReporter { url }
+    }
+}
+
+impl Transport for WebSocketReporter {
+    fn serialize(&self, reports: &[PanicReport]) -> Option<String> {
+        imp::to_json(reports, true)
+    }
+
+    fn deliver(&self, payload: &str) -> bool {
+        imp::send_websocket(&self.url, payload)
+    }
+}
+
+impl Reporter for WebSocketReporter {
+    fn report(&self, report: &PanicReport) {
+        transport::send(self, imp::report_to_json(report, true));
+    }
+
+    fn is_remote(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,47 @@
+use super::{imp, PanicReport, Reporter};
+
+/// A reporter that, inside a dedicated Web Worker, posts panics to the main
+/// thread, where they are harder to miss than in the worker's own console.
+///
+/// Outside of a dedicated worker, and on non-wasm targets, this does nothing,
+/// so it is safe to install it from code that runs in both. Add it alongside
+/// a [`ConsoleReporter`](struct.ConsoleReporter.html) to keep logging panics
+/// in the worker too:
+///
+/// ```
+/// use console_error_panic_hook::{ConsoleReporter, WorkerReporter};
+///
+/// console_error_panic_hook::builder()
+///     .with_reporter(Box::new(ConsoleReporter::new()))
+///     .with_reporter(Box::new(WorkerReporter::new()))
+///     .install();
+/// ```
+///
+/// Each panic is posted as `{ type: "console_error_panic_hook", report }`,
+/// where `report` holds the `message`, `payload`, `file`, `line`, `column`,
+/// `thread`, `stack`, `timestamp`, `uptime`, `memory`, `environment`, `build`,
+/// and `moduleName` of the [`PanicReport`](struct.PanicReport.html). Listen
+/// for them on the main thread with:
+///
+/// ```js
+/// worker.addEventListener("message", ({ data }) => {
+///   if (data && data.type === "console_error_panic_hook") {
+///     console.error(`A worker panicked:\n\n${data.report.message}`);
+///   }
+/// });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct WorkerReporter;
+
+impl WorkerReporter {
+    /// Create a reporter that posts panics to the main thread.
+    pub fn new() -> WorkerReporter {
+        WorkerReporter
+    }
+}
+
+impl Reporter for WorkerReporter {
+    fn report(&self, report: &PanicReport) {
+        imp::post_to_main_thread(report);
+    }
+}
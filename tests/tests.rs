@@ -1,9 +1,25 @@
 extern crate console_error_panic_hook;
+#[cfg(feature = "regex")]
+extern crate regex;
+
+use console_error_panic_hook::{
+    BufferReporter, BuildInfo, FnReporter, FunctionNames, PanicHookInfo, PanicReport, Reporter,
+    Runtime, Transport, TransportReporter, WriterReporter,
+};
 
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, Read, Write};
 use std::panic;
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Tests that install a hook and then panic need the global panic hook to
+/// themselves.
+fn lock_hook() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
 
 #[test]
 fn cargo_readme_up_to_date() {
@@ -37,12 +53,1079 @@ fn cargo_readme_up_to_date() {
 
 #[test]
 fn can_set_as_hook() {
+    let _lock = lock_hook();
     panic::set_hook(Box::new(console_error_panic_hook::hook));
 }
 
 #[test]
 fn can_set_once() {
+    let _lock = lock_hook();
     for _ in 0..10 {
         console_error_panic_hook::set_once();
     }
 }
+
+#[test]
+fn can_install_from_builder() {
+    let _lock = lock_hook();
+    console_error_panic_hook::builder()
+        .with_prefix("[tests]")
+        .with_verbosity(console_error_panic_hook::Verbosity::Short)
+        .install();
+}
+
+#[test]
+fn uses_custom_formatter() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn formatter(info: &PanicHookInfo) -> String {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        format!("formatted: {}", info)
+    }
+
+    let _lock = lock_hook();
+    console_error_panic_hook::builder()
+        .with_formatter(formatter)
+        .install();
+    let _ = panic::catch_unwind(|| panic!("uses_custom_formatter"));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn format_panic_includes_payload_and_location() {
+    let _lock = lock_hook();
+    let formatted = Arc::new(Mutex::new(String::new()));
+    {
+        let formatted = formatted.clone();
+        panic::set_hook(Box::new(move |info| {
+            *formatted.lock().unwrap() = console_error_panic_hook::format_panic(info);
+        }));
+    }
+    let _ = panic::catch_unwind(|| panic!("format_panic_includes_payload_and_location"));
+    let _ = panic::take_hook();
+
+    let formatted = formatted.lock().unwrap();
+    assert!(formatted.contains("format_panic_includes_payload_and_location"));
+    assert!(formatted.contains("tests/tests.rs"));
+}
+
+/// A reporter that keeps every report it is given.
+struct Recorder(Arc<Mutex<Vec<PanicReport>>>);
+
+impl Reporter for Recorder {
+    fn report(&self, report: &PanicReport) {
+        self.0.lock().unwrap().push(report.clone());
+    }
+}
+
+#[test]
+fn uses_custom_reporter() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("uses_custom_reporter"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].message().contains("uses_custom_reporter"));
+}
+
+#[test]
+fn uses_closure_reporter() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    {
+        let reports = reports.clone();
+        console_error_panic_hook::builder()
+            .with_reporter(Box::new(FnReporter::new(move |msg: &str| {
+                reports.lock().unwrap().push(msg.to_string());
+            })))
+            .install();
+    }
+    let _ = panic::catch_unwind(|| panic!("uses_closure_reporter"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].contains("uses_closure_reporter"));
+}
+
+#[test]
+fn fans_out_to_every_reporter() {
+    let _lock = lock_hook();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = |calls: &Arc<AtomicUsize>| {
+        let calls = calls.clone();
+        Box::new(FnReporter::new(move |_: &str| {
+            calls.fetch_add(1, Ordering::SeqCst);
+        }))
+    };
+    console_error_panic_hook::builder()
+        .with_reporter(counter(&calls))
+        .with_reporter(counter(&calls))
+        .with_reporter(counter(&calls))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("fans_out_to_every_reporter"));
+    let _ = panic::take_hook();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn chains_previous_hook() {
+    let _lock = lock_hook();
+    let previous_calls = Arc::new(AtomicUsize::new(0));
+    let reports = Arc::new(AtomicUsize::new(0));
+    {
+        let previous_calls = previous_calls.clone();
+        panic::set_hook(Box::new(move |_| {
+            previous_calls.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    {
+        let reports = reports.clone();
+        console_error_panic_hook::builder()
+            .with_reporter(Box::new(FnReporter::new(move |_: &str| {
+                reports.fetch_add(1, Ordering::SeqCst);
+            })))
+            .with_previous_hook(true)
+            .install();
+    }
+    let _ = panic::catch_unwind(|| panic!("chains_previous_hook"));
+    let _ = panic::take_hook();
+
+    assert_eq!(reports.load(Ordering::SeqCst), 1);
+    assert_eq!(previous_calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn scoped_hook_restores_previous_hook() {
+    let _lock = lock_hook();
+    let previous_calls = Arc::new(AtomicUsize::new(0));
+    let reports = Arc::new(AtomicUsize::new(0));
+    {
+        let previous_calls = previous_calls.clone();
+        panic::set_hook(Box::new(move |_| {
+            previous_calls.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    {
+        let reports = reports.clone();
+        let _guard = console_error_panic_hook::builder()
+            .with_reporter(Box::new(FnReporter::new(move |_: &str| {
+                reports.fetch_add(1, Ordering::SeqCst);
+            })))
+            .install_scoped();
+        let _ = panic::catch_unwind(|| panic!("scoped_hook_restores_previous_hook"));
+    }
+    let _ = panic::catch_unwind(|| panic!("scoped_hook_restores_previous_hook"));
+    let _ = panic::take_hook();
+
+    assert_eq!(reports.load(Ordering::SeqCst), 1);
+    assert_eq!(previous_calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn unset_restores_previous_hook() {
+    let _lock = lock_hook();
+    let previous_calls = Arc::new(AtomicUsize::new(0));
+    let reports = Arc::new(AtomicUsize::new(0));
+    {
+        let previous_calls = previous_calls.clone();
+        panic::set_hook(Box::new(move |_| {
+            previous_calls.fetch_add(1, Ordering::SeqCst);
+        }));
+    }
+    {
+        let reports = reports.clone();
+        console_error_panic_hook::builder()
+            .with_reporter(Box::new(FnReporter::new(move |_: &str| {
+                reports.fetch_add(1, Ordering::SeqCst);
+            })))
+            .install();
+    }
+    console_error_panic_hook::unset();
+    let _ = panic::catch_unwind(|| panic!("unset_restores_previous_hook"));
+    let _ = panic::take_hook();
+
+    assert_eq!(reports.load(Ordering::SeqCst), 0);
+    assert_eq!(previous_calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn is_set_tracks_installation() {
+    let _lock = lock_hook();
+    console_error_panic_hook::unset();
+    assert!(!console_error_panic_hook::is_set());
+    {
+        let _guard = console_error_panic_hook::scoped();
+        assert!(console_error_panic_hook::is_set());
+    }
+    assert!(!console_error_panic_hook::is_set());
+    console_error_panic_hook::builder().install();
+    assert!(console_error_panic_hook::is_set());
+    console_error_panic_hook::unset();
+    assert!(!console_error_panic_hook::is_set());
+}
+
+#[test]
+fn does_not_report_while_disabled() {
+    let _lock = lock_hook();
+    let reports = Arc::new(AtomicUsize::new(0));
+    {
+        let reports = reports.clone();
+        console_error_panic_hook::builder()
+            .with_reporter(Box::new(FnReporter::new(move |_: &str| {
+                reports.fetch_add(1, Ordering::SeqCst);
+            })))
+            .install();
+    }
+    console_error_panic_hook::set_enabled(false);
+    let _ = panic::catch_unwind(|| panic!("does_not_report_while_disabled"));
+    console_error_panic_hook::set_enabled(true);
+    let _ = panic::catch_unwind(|| panic!("does_not_report_while_disabled"));
+    let _ = panic::take_hook();
+
+    assert_eq!(reports.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn reports_structured_fields() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    {
+        let reports = reports.clone();
+        console_error_panic_hook::builder()
+            .with_reporter(Box::new(Recorder(reports)))
+            .install();
+    }
+    let line = line!() + 1;
+    let _ = panic::catch_unwind(|| panic!("reports_structured_fields {}", 42));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let report = &reports[0];
+    assert_eq!(report.payload(), Some("reports_structured_fields 42"));
+    assert_eq!(report.file(), Some("tests/tests.rs"));
+    assert_eq!(report.line(), Some(line));
+    assert!(report.column().is_some());
+    assert!(report.timestamp().is_some());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn serializes_reports_as_json() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("serializes_reports_as_json"));
+    let _ = panic::take_hook();
+
+    let json = reports.lock().unwrap()[0].to_json();
+    assert!(!json.contains('\n'));
+    assert!(json.contains(r#""payload":"serializes_reports_as_json""#));
+    assert!(json.contains(r#""file":"tests/tests.rs""#));
+}
+
+#[test]
+fn reads_function_names_from_name_section() {
+    let mut module = b"\0asm\x01\0\0\0".to_vec();
+    // A `name` custom section, with a function names subsection naming
+    // functions 0 and 7.
+    module.extend_from_slice(b"\x00\x15\x04name");
+    module.extend_from_slice(b"\x01\x0e\x02\x00\x04main\x07\x05help8");
+    let names = FunctionNames::from_module(&module).expect("should parse the name section");
+    assert_eq!(names.get(0), Some("main"));
+    assert_eq!(names.get(7), Some("help8"));
+    assert_eq!(names.get(1), None);
+
+    assert!(FunctionNames::from_module(b"not wasm").is_none());
+}
+
+#[test]
+fn links_to_source_location() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_source_link("vscode://file/project/{file}:{line}:{column}")
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let line = line!() + 1;
+    let _ = panic::catch_unwind(|| panic!("links_to_source_location"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let link = reports[0].source_link().expect("should have a source link");
+    let expected = format!("vscode://file/project/tests/tests.rs:{}:", line);
+    assert!(link.starts_with(&expected), "unexpected link {}", link);
+    assert!(reports[0].message().contains(link));
+}
+
+#[test]
+fn includes_timestamps() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_timestamp(true)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("includes_timestamps"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let message = reports[0].message();
+    let (_, time) = message
+        .split_once("\n\nTime: ")
+        .expect("should have a time");
+    let time = time.lines().next().unwrap();
+    assert_eq!(time.len(), "2024-01-31T12:34:56.789Z".len());
+    assert!(time.ends_with('Z'), "unexpected time {:?}", time);
+    assert!(reports[0].timestamp().is_some());
+    assert_eq!(reports[0].uptime(), None);
+}
+
+#[test]
+fn stamps_build_info() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_build_info(BuildInfo::new("1.2.3").with_git_hash("abc1234"))
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("stamps_build_info"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let build = reports[0].build().expect("should have build info");
+    assert_eq!(build.version(), "1.2.3");
+    assert_eq!(build.git_hash(), Some("abc1234"));
+    assert_eq!(build.build_time(), None);
+    assert!(reports[0].message().contains("\n\nBuild: 1.2.3 (abc1234)"));
+}
+
+#[test]
+fn names_the_module() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_prefix("[app]")
+        .with_module_name("editor")
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("names_the_module"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports[0].module_name(), Some("editor"));
+    assert!(reports[0].message().starts_with("[app] [editor] "));
+}
+
+#[test]
+fn truncates_long_messages() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_max_length(100)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("{}", "é".repeat(1000)));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(
+        reports[0].payload(),
+        Some(&*format!("{}… [1900 more bytes]", "é".repeat(50)))
+    );
+    let message = reports[0].message();
+    assert!(message.contains("panicked at"));
+    assert!(
+        message.lines().nth(1).unwrap().ends_with(" more bytes]"),
+        "unexpected message {:?}",
+        message
+    );
+}
+
+#[test]
+fn captures_native_backtrace() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_backtrace(true)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("captures_native_backtrace"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let stack = reports[0].stack().expect("should capture a backtrace");
+    assert!(stack.contains("captures_native_backtrace"));
+    assert!(!stack.contains("console_error_panic_hook::"));
+    assert!(reports[0].message().contains(stack));
+}
+
+#[test]
+fn writes_to_any_writer() {
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let _lock = lock_hook();
+    let buffer = SharedBuffer::default();
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(WriterReporter::new(Box::new(buffer.clone()))))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("writes_to_any_writer"));
+    let _ = panic::take_hook();
+
+    let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(written.contains("writes_to_any_writer"));
+    assert!(written.ends_with('\n'));
+}
+
+#[test]
+fn aborts_after_reporting() {
+    // Aborting would take the whole test binary down with it, so the panic
+    // happens in a copy of this test running in a child process.
+    if std::env::var_os("ABORTS_AFTER_REPORTING").is_some() {
+        console_error_panic_hook::builder()
+            .with_abort(true)
+            .install();
+        let _ = panic::catch_unwind(|| panic!("aborts_after_reporting"));
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "aborts_after_reporting", "--nocapture"])
+        .env("ABORTS_AFTER_REPORTING", "1")
+        .output()
+        .expect("should run the test binary");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("aborts_after_reporting"));
+}
+
+#[test]
+fn logs_panics_while_reporting() {
+    // Were the panicking reporter not contained, the test binary would abort,
+    // so this happens in a copy of this test running in a child process.
+    if std::env::var_os("LOGS_PANICS_WHILE_REPORTING").is_some() {
+        console_error_panic_hook::builder()
+            .with_reporter(Box::new(FnReporter::new(|_: &str| {
+                panic!("reporter broke")
+            })))
+            .install();
+        let _ = panic::catch_unwind(|| panic!("reported before the reporter broke"));
+        let _ = panic::take_hook();
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "logs_panics_while_reporting", "--nocapture"])
+        .env("LOGS_PANICS_WHILE_REPORTING", "1")
+        .output()
+        .expect("should run the test binary");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("panic while reporting panic: panicked at tests/tests.rs:"));
+    assert!(stderr.contains("reported before the reporter broke"));
+    assert!(!stderr.contains("\nreporter broke"));
+}
+
+#[test]
+fn names_the_panicking_thread() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = std::thread::Builder::new()
+        .name("worker".to_string())
+        .spawn(|| panic!("names_the_panicking_thread"))
+        .unwrap()
+        .join();
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports[0].thread(), "worker");
+    assert!(reports[0]
+        .message()
+        .starts_with("thread 'worker' panicked at"));
+}
+
+#[test]
+fn buffers_the_most_recent_panics() {
+    let _lock = lock_hook();
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(BufferReporter::with_capacity(2)))
+        .install();
+    for i in 0..3 {
+        let _ = panic::catch_unwind(|| panic!("buffered {}", i));
+    }
+    let _ = panic::take_hook();
+
+    let payloads: Vec<_> = BufferReporter::drain()
+        .iter()
+        .map(|report| report.payload().unwrap().to_string())
+        .collect();
+    assert_eq!(payloads, ["buffered 1", "buffered 2"]);
+    assert!(BufferReporter::drain().is_empty());
+}
+
+#[test]
+fn detects_native_runtime() {
+    assert_eq!(Runtime::detect(), Runtime::Native);
+}
+
+#[test]
+fn transports_retry_and_drop_failed_batches() {
+    struct Recording {
+        up: AtomicBool,
+        attempts: AtomicUsize,
+        delivered: Mutex<Vec<String>>,
+    }
+
+    impl Transport for Recording {
+        fn serialize(&self, reports: &[PanicReport]) -> Option<String> {
+            let payloads: Vec<_> = reports.iter().map(|r| r.payload().unwrap()).collect();
+            Some(payloads.join(","))
+        }
+
+        fn deliver(&self, payload: &str) -> bool {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            if self.up.load(Ordering::SeqCst) {
+                self.delivered.lock().unwrap().push(payload.to_string());
+            }
+            self.up.load(Ordering::SeqCst)
+        }
+    }
+
+    let _lock = lock_hook();
+    let reporter = Arc::new(
+        TransportReporter::new(Recording {
+            up: AtomicBool::new(false),
+            attempts: AtomicUsize::new(0),
+            delivered: Mutex::new(Vec::new()),
+        })
+        .with_max_retries(0),
+    );
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(reporter.clone()))
+        .install();
+    let transport = reporter.transport();
+
+    let _ = panic::catch_unwind(|| panic!("dropped"));
+    assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+    reporter.flush();
+    assert_eq!(transport.attempts.load(Ordering::SeqCst), 1);
+
+    transport.up.store(true, Ordering::SeqCst);
+    let _ = panic::catch_unwind(|| panic!("sent"));
+    let _ = panic::take_hook();
+    assert_eq!(*transport.delivered.lock().unwrap(), ["sent"]);
+}
+
+#[test]
+fn collapses_repeated_panics() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_dedup(true)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    for _ in 0..3 {
+        let _ = panic::catch_unwind(|| panic!("every frame"));
+    }
+    let _ = panic::catch_unwind(|| panic!("something else"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let payloads: Vec<_> = reports.iter().map(|report| report.payload()).collect();
+    assert_eq!(payloads, [Some("every frame"), Some("something else")]);
+}
+
+#[test]
+fn rate_limits_reports() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_rate_limit(2, None)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    for i in 0..5 {
+        let _ = panic::catch_unwind(|| panic!("retry {}", i));
+    }
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let payloads: Vec<_> = reports.iter().map(|report| report.payload()).collect();
+    assert_eq!(payloads, [Some("retry 0"), Some("retry 1")]);
+}
+
+#[test]
+fn only_reports_the_first_panic() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_first_panic_only(true)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let _ = panic::catch_unwind(|| panic!("first"));
+    let _ = panic::catch_unwind(|| panic!("second"));
+    let _ = panic::catch_unwind(|| panic!("third"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].payload(), Some("first"));
+}
+
+#[test]
+#[cfg(has_alloc_error_hook)]
+fn reports_allocation_failures() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    console_error_panic_hook::set_alloc_error_hook();
+    let layout = std::alloc::Layout::from_size_align(1 << 40, 1).unwrap();
+    let _ = panic::catch_unwind(|| std::alloc::handle_alloc_error(layout));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports[0].payload(), Some("memory allocation failed"));
+}
+
+#[test]
+fn reads_runtime_config_from_the_environment() {
+    struct Remote(Arc<AtomicUsize>);
+
+    impl Reporter for Remote {
+        fn report(&self, _report: &PanicReport) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn is_remote(&self) -> bool {
+            true
+        }
+    }
+
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let remote = Arc::new(AtomicUsize::new(0));
+    std::env::set_var("CONSOLE_ERROR_PANIC_HOOK", "short,no-remote");
+    console_error_panic_hook::builder()
+        .with_runtime_config(true)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .with_reporter(Box::new(Remote(remote.clone())))
+        .install();
+    std::env::remove_var("CONSOLE_ERROR_PANIC_HOOK");
+    let _ = panic::catch_unwind(|| panic!("configured at runtime"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].stack(), None);
+    assert_eq!(remote.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn follows_the_backtrace_switch() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_backtrace_switch(true)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let original = std::env::var_os("RUST_BACKTRACE");
+    let context = console_error_panic_hook::context("switching");
+    std::env::set_var("RUST_BACKTRACE", "0");
+    let _ = panic::catch_unwind(|| panic!("switched off"));
+    std::env::set_var("RUST_BACKTRACE", "1");
+    let _ = panic::catch_unwind(|| panic!("switched on"));
+    drop(context);
+    match original {
+        Some(original) => std::env::set_var("RUST_BACKTRACE", original),
+        None => std::env::remove_var("RUST_BACKTRACE"),
+    }
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].stack(), None);
+    assert!(reports[0].environment().is_none());
+    assert!(reports[0].context().is_empty());
+    assert!(!reports[0].message().contains("Context:"));
+    assert!(reports[1].stack().is_some());
+    assert!(reports[1].environment().is_some());
+    assert_eq!(reports[1].context(), ["switching"]);
+}
+
+#[cfg(feature = "macros")]
+#[test]
+fn main_attribute_keeps_the_body() {
+    #[console_error_panic_hook::main]
+    fn start() -> u32 {
+        42
+    }
+
+    let _lock = lock_hook();
+    let _ = panic::take_hook();
+    assert_eq!(start(), 42);
+}
+
+#[test]
+fn catches_and_reports_panics() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_verbosity(console_error_panic_hook::Verbosity::Short)
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    let caught = console_error_panic_hook::catch_and_report(|| -> u32 { panic!("caught") });
+    let returned = console_error_panic_hook::catch_and_report(|| 7);
+    let _ = panic::take_hook();
+    let without_hook = console_error_panic_hook::catch_and_report(|| -> u32 { panic!("unhooked") });
+
+    let caught = caught.unwrap_err();
+    assert_eq!(caught.payload(), Some("caught"));
+    assert_eq!(caught.message(), reports.lock().unwrap()[0].message());
+    assert_eq!(returned.unwrap(), 7);
+    let without_hook = without_hook.unwrap_err();
+    assert_eq!(without_hook.payload(), Some("unhooked"));
+    assert_eq!(without_hook.file(), None);
+}
+
+#[test]
+fn keeps_the_last_panic() {
+    let _lock = lock_hook();
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(Arc::new(Mutex::new(Vec::new())))))
+        .install();
+    let _ = console_error_panic_hook::take_last_panic();
+    let _ = panic::catch_unwind(|| panic!("first"));
+    let _ = panic::catch_unwind(|| panic!("second"));
+    let _ = panic::take_hook();
+
+    let last = console_error_panic_hook::last_panic().unwrap();
+    assert_eq!(last.payload(), Some("second"));
+    let taken = console_error_panic_hook::take_last_panic().unwrap();
+    assert_eq!(taken.message(), last.message());
+    assert!(console_error_panic_hook::last_panic().is_none());
+}
+
+#[test]
+fn counts_panics() {
+    let _lock = lock_hook();
+    console_error_panic_hook::builder()
+        .with_dedup(true)
+        .with_reporter(Box::new(Recorder(Arc::new(Mutex::new(Vec::new())))))
+        .install();
+    let before = console_error_panic_hook::panic_count();
+    for _ in 0..3 {
+        let _ = panic::catch_unwind(|| panic!("counted"));
+    }
+    let _ = panic::take_hook();
+
+    assert_eq!(console_error_panic_hook::panic_count(), before + 3);
+}
+
+#[test]
+fn includes_breadcrumbs() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    console_error_panic_hook::set_max_breadcrumbs(2);
+    console_error_panic_hook::add_breadcrumb("navigation", "opened /");
+    console_error_panic_hook::add_breadcrumb("navigation", "opened /settings");
+    console_error_panic_hook::add_breadcrumb("input", "clicked save");
+    let _ = panic::catch_unwind(|| panic!("after breadcrumbs"));
+    console_error_panic_hook::clear_breadcrumbs();
+    console_error_panic_hook::set_max_breadcrumbs(20);
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    let breadcrumbs = reports[0].breadcrumbs();
+    assert_eq!(breadcrumbs.len(), 2);
+    assert_eq!(breadcrumbs[0].message(), "opened /settings");
+    assert_eq!(breadcrumbs[1].category(), "input");
+    assert!(reports[0].message().contains("\n\nBreadcrumbs:\n  "));
+    assert!(reports[0].message().contains(" [input] clicked save"));
+}
+
+#[test]
+fn includes_context() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    {
+        let _gone = console_error_panic_hook::context("starting up");
+    }
+    let _outer = console_error_panic_hook::context("loading save-1.dat");
+    let _ = panic::catch_unwind(|| {
+        let _inner = console_error_panic_hook::context("decoding chunk 3");
+        panic!("with context");
+    });
+    drop(_outer);
+    let _ = panic::catch_unwind(|| panic!("without context"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(
+        reports[0].context(),
+        ["loading save-1.dat", "decoding chunk 3"]
+    );
+    assert!(reports[0]
+        .message()
+        .contains("\n\nContext:\n  loading save-1.dat\n  decoding chunk 3"));
+    assert!(reports[1].context().is_empty());
+}
+
+#[test]
+fn includes_state_from_providers() {
+    fn route() -> Vec<(String, String)> {
+        vec![("route".to_string(), "/settings".to_string())]
+    }
+
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .install();
+    console_error_panic_hook::add_context_provider(broken_provider);
+    console_error_panic_hook::add_context_provider(route);
+    let _ = panic::catch_unwind(|| panic!("with state"));
+    console_error_panic_hook::clear_context_providers();
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    // The broken provider's panic isn't reported as one of its own.
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].payload(), Some("with state"));
+    assert_eq!(
+        reports[0].state(),
+        [
+            ("context provider 1".to_string(), "panicked".to_string()),
+            ("route".to_string(), "/settings".to_string()),
+        ]
+    );
+    assert!(reports[0]
+        .message()
+        .contains("\n\nState:\n  context provider 1: panicked\n  route: /settings"));
+}
+
+fn broken_provider() -> Vec<(String, String)> {
+    panic!("provider broke")
+}
+
+#[test]
+fn ignores_panicking_providers_when_aborting() {
+    // Aborting would take the whole test binary down with it, so the panic
+    // happens in a copy of this test running in a child process.
+    if std::env::var_os("IGNORES_PANICKING_PROVIDERS_WHEN_ABORTING").is_some() {
+        console_error_panic_hook::builder()
+            .with_abort(true)
+            .install();
+        console_error_panic_hook::add_context_provider(broken_provider);
+        let _ = panic::catch_unwind(|| panic!("reported before aborting"));
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "ignores_panicking_providers_when_aborting",
+            "--nocapture",
+        ])
+        .env("IGNORES_PANICKING_PROVIDERS_WHEN_ABORTING", "1")
+        .output()
+        .expect("should run the test binary");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("reported before aborting"));
+    assert!(stderr.contains("context provider 1: panicked"));
+    assert!(!stderr.contains("provider broke"));
+    assert!(!stderr.contains("panic while reporting panic"));
+}
+
+#[test]
+fn marks_panicking_providers_as_failed_before_aborting() {
+    fn noisy_provider() -> Vec<(String, String)> {
+        eprintln!("provider started");
+        panic!("provider broke")
+    }
+
+    // As above, aborting would take the whole test binary down with it.
+    if std::env::var_os("MARKS_PANICKING_PROVIDERS_AS_FAILED").is_some() {
+        console_error_panic_hook::builder()
+            .with_abort(true)
+            .with_reporter(Box::new(FnReporter::new(|message: &str| {
+                eprintln!(
+                    "delivered after {} panic(s):\n{}",
+                    console_error_panic_hook::panic_count(),
+                    message
+                );
+            })))
+            .install();
+        console_error_panic_hook::add_context_provider(noisy_provider);
+        let _ = panic::catch_unwind(|| panic!("the only panic"));
+        eprintln!("still running");
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "marks_panicking_providers_as_failed_before_aborting",
+            "--nocapture",
+        ])
+        .env("MARKS_PANICKING_PROVIDERS_AS_FAILED", "1")
+        .output()
+        .expect("should run the test binary");
+
+    // The provider's panic neither aborts nor counts: the one report is
+    // delivered after it, marked as failed, and only then is there an abort.
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let started = stderr.find("provider started").unwrap();
+    let delivered = stderr.find("delivered after 1 panic(s):").unwrap();
+    assert!(started < delivered);
+    assert_eq!(stderr.matches("delivered after").count(), 1);
+    assert!(stderr[delivered..].contains("the only panic"));
+    assert!(stderr[delivered..].contains("context provider 1: panicked"));
+    assert!(!stderr.contains("provider broke"));
+    assert!(!stderr.contains("still running"));
+}
+
+#[test]
+fn ignores_panicking_providers_in_the_previous_hook() {
+    // The previous hook is the standard library's, which prints to the real
+    // `stderr`, so the panic happens in a child process to capture that.
+    if std::env::var_os("IGNORES_PANICKING_PROVIDERS_IN_THE_PREVIOUS_HOOK").is_some() {
+        console_error_panic_hook::builder()
+            .with_previous_hook(true)
+            .install();
+        console_error_panic_hook::add_context_provider(broken_provider);
+        let _ = panic::catch_unwind(|| panic!("chained to the previous hook"));
+        return;
+    }
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "--exact",
+            "ignores_panicking_providers_in_the_previous_hook",
+            "--nocapture",
+        ])
+        .env("IGNORES_PANICKING_PROVIDERS_IN_THE_PREVIOUS_HOOK", "1")
+        .output()
+        .expect("should run the test binary");
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("chained to the previous hook"));
+    assert!(stderr.contains("context provider 1: panicked"));
+    assert!(!stderr.contains("provider broke"));
+    assert!(!stderr.contains("panic while reporting panic"));
+}
+
+#[test]
+fn redacts_sensitive_text() {
+    fn session() -> Vec<(String, String)> {
+        vec![("session hunter2".to_string(), "user hunter2".to_string())]
+    }
+
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .with_redaction("hunter2")
+        .with_redaction("/home/alice")
+        .install();
+    console_error_panic_hook::add_context_provider(session);
+    console_error_panic_hook::add_breadcrumb("login hunter2", "signed in as hunter2");
+    let _context = console_error_panic_hook::context("reading /home/alice/notes.txt");
+    let _ = panic::catch_unwind(|| panic!("bad token hunter2 for hunter2"));
+    drop(_context);
+    console_error_panic_hook::clear_breadcrumbs();
+    console_error_panic_hook::clear_context_providers();
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(
+        reports[0].payload(),
+        Some("bad token [redacted] for [redacted]")
+    );
+    assert_eq!(reports[0].context(), ["reading [redacted]/notes.txt"]);
+    assert_eq!(
+        reports[0].state(),
+        [(
+            "session [redacted]".to_string(),
+            "user [redacted]".to_string()
+        )]
+    );
+    let breadcrumb = &reports[0].breadcrumbs()[0];
+    assert_eq!(breadcrumb.category(), "login [redacted]");
+    assert_eq!(breadcrumb.message(), "signed in as [redacted]");
+    assert!(!reports[0].message().contains("hunter2"));
+    assert!(!reports[0].message().contains("/home/alice"));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn redacts_text_matching_patterns() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .with_redaction_pattern(regex::Regex::new(r"sk_[0-9a-f]+").unwrap())
+        .install();
+    let _ = panic::catch_unwind(|| panic!("rejected key sk_3f9a01"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports[0].payload(), Some("rejected key [redacted]"));
+    assert!(!reports[0].message().contains("sk_3f9a01"));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn redacts_each_match_once() {
+    let _lock = lock_hook();
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    console_error_panic_hook::builder()
+        .with_reporter(Box::new(Recorder(reports.clone())))
+        .with_redaction("secret")
+        .with_redaction("dact")
+        .with_redaction_pattern(regex::Regex::new("[a-z]{8,}").unwrap())
+        .install();
+    let _ = panic::catch_unwind(|| panic!("x secret y"));
+    let _ = panic::catch_unwind(|| panic!("token abcdefghij"));
+    let _ = panic::take_hook();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports[0].payload(), Some("x [redacted] y"));
+    assert!(reports[0].message().contains("x [redacted] y"));
+    assert_eq!(reports[1].payload(), Some("token [redacted]"));
+    assert!(reports[1].message().contains("token [redacted]"));
+    assert!(!reports[1].message().contains("[["));
+}